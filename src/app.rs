@@ -1,10 +1,15 @@
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
 use ratatui::widgets::ScrollbarState;
-use sysinfo::System;
+use sysinfo::Uid;
 
-use crate::filter;
-use crate::scanner::{self, DevProcess};
+use crate::environ::EnvView;
+use crate::inspect::PortReport;
+use crate::scanner::{self, DevProcess, MachineCpu};
+use crate::source::Signal;
+use crate::tree::{self, ProcNode};
+use crate::worker::{ScanOutput, ScanRequest, Worker};
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum OwlMood {
@@ -20,31 +25,188 @@ pub enum OwlMood {
 pub enum SortColumn {
     Pid,
     Name,
+    Project,
     Port,
     Proto,
+    Conn,
     Cpu,
     Memory,
     Uptime,
 }
 
+#[derive(Clone, Copy, PartialEq, Debug, clap::ValueEnum)]
+pub enum ProtoFilter {
+    Tcp,
+    Udp,
+    Unix,
+}
+
+impl ProtoFilter {
+    pub fn label(self) -> &'static str {
+        match self {
+            ProtoFilter::Tcp => "TCP",
+            ProtoFilter::Udp => "UDP",
+            ProtoFilter::Unix => "UNIX",
+        }
+    }
+
+    pub fn matches(self, protocol: &str) -> bool {
+        protocol.eq_ignore_ascii_case(self.label())
+    }
+}
+
+/// Figure the MEMORY column shows and sorts by
+#[derive(Clone, Copy, PartialEq, Debug, clap::ValueEnum)]
+pub enum MemoryMetric {
+    Rss,
+    Pss,
+    Uss,
+    Swap,
+    Virtual,
+}
+
+impl MemoryMetric {
+    pub fn column(self) -> &'static str {
+        match self {
+            MemoryMetric::Rss => "MEMORY",
+            MemoryMetric::Pss => "MEM PSS",
+            MemoryMetric::Uss => "MEM USS",
+            MemoryMetric::Swap => "SWAP",
+            MemoryMetric::Virtual => "VIRT",
+        }
+    }
+
+    /// `None` when the figure isn't readable for this process
+    pub fn of(self, process: &DevProcess) -> Option<u64> {
+        let detail = process.memory_detail;
+        match self {
+            MemoryMetric::Rss => Some(process.memory_bytes),
+            MemoryMetric::Pss => detail.pss,
+            MemoryMetric::Uss => detail.uss,
+            MemoryMetric::Swap => detail.swap,
+            MemoryMetric::Virtual => detail.virtual_size,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            MemoryMetric::Rss => MemoryMetric::Pss,
+            MemoryMetric::Pss => MemoryMetric::Uss,
+            MemoryMetric::Uss => MemoryMetric::Swap,
+            MemoryMetric::Swap => MemoryMetric::Virtual,
+            MemoryMetric::Virtual => MemoryMetric::Rss,
+        }
+    }
+}
+
+/// What 100% means in the CPU% column
+#[derive(Clone, Copy, PartialEq, Debug, clap::ValueEnum)]
+pub enum CpuMode {
+    /// One logical core, as in top; multi-threaded servers go past 100%
+    PerCore,
+    /// Every logical core on the machine
+    Machine,
+}
+
+/// Which key bindings apply; modal popups take over the keyboard
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InputMode {
+    Normal,
+    Confirm,
+    /// The environment pane is open
+    Env,
+    /// Typing into the environment pane's search box
+    EnvSearch,
+    /// The port inspection popup is open
+    Inspect,
+    /// The key list popup is open
+    Help,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ContainerFilter {
+    Show,
+    Hide,
+    Only,
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum SortDirection {
     Ascending,
     Descending,
 }
 
+pub struct KillTarget {
+    pub pid: u32,
+    pub name: String,
+    pub ports: String,
+    /// Every process to signal when killing the whole tree, root last;
+    /// empty for a plain kill of the listener itself
+    pub tree: Vec<ProcNode>,
+    /// Owner's name when the target belongs to another user
+    pub foreign_owner: Option<String>,
+    /// (id, name) of the Docker container behind the listener, offering
+    /// stop/restart as an alternative to signalling the PID
+    pub container: Option<(String, String)>,
+    /// `Term` to kill, `Stop` to suspend
+    pub signal: Signal,
+}
+
 pub enum Message {
     Tick,
     Quit,
     NavigateUp,
     NavigateDown,
     Kill,
+    KillTree,
     ConfirmKill,
+    StopContainer,
+    RestartContainer,
     CancelKill,
     Refresh,
     ToggleAll,
     CycleSort,
     ToggleSortDirection,
+    ToggleGroup,
+    ToggleExpand,
+    CycleProto,
+    ScrollLeft,
+    ScrollRight,
+    ToggleTree,
+    ToggleMine,
+    CycleContainers,
+    ToggleIo,
+    ToggleFds,
+    ToggleUnix,
+    /// SIGSTOP the selected process, after confirming
+    Suspend,
+    /// SIGCONT the selected process
+    Resume,
+    ShowEnv,
+    CloseEnv,
+    StartEnvSearch,
+    EnvSearchInput(char),
+    EnvSearchBackspace,
+    /// Leave the search box, keeping the query
+    EndEnvSearch,
+    /// Leave the search box and drop the query
+    CancelEnvSearch,
+    RevealSecrets,
+    /// Show every socket on the selected (or filtered) port
+    InspectPort,
+    CloseInspect,
+    ToggleHelp,
+    CycleMemoryMetric,
+    ToggleCpuMode,
+    ToggleMemoryDetail,
+    /// A background scan finished
+    Scanned(Box<ScanOutput>),
+    /// The worker worked out which PIDs killing this PID's tree signals
+    KillOrder(u32, Vec<ProcNode>),
+    /// A container stop or restart finished, with the status to show
+    ContainerDone(String),
+    /// The worker read every socket on a port
+    PortInspected(u16, Result<PortReport, String>),
 }
 
 pub struct App {
@@ -55,11 +217,11 @@ pub struct App {
     pub sort_column: SortColumn,
     pub sort_direction: SortDirection,
     pub show_kill_confirm: bool,
-    pub kill_target: Option<(u32, String, u16)>,
+    pub kill_target: Option<KillTarget>,
     pub status_message: Option<String>,
     pub status_timer: u8,
-    pub system: System,
     pub filter_port: Option<u16>,
+    pub filter_proto: Option<ProtoFilter>,
     pub last_refresh: Instant,
     pub scrollbar_state: ScrollbarState,
     pub tick_rate_secs: u64,
@@ -67,49 +229,186 @@ pub struct App {
     pub owl_mood: OwlMood,
     pub owl_mood_until: Instant,
     pub last_action: Instant,
+    pub grouped: bool,
+    pub expanded: HashSet<u32>,
+    pub cmd_scroll: usize,
+    pub probe_http: bool,
+    pub tree_view: bool,
+    pub current_uid: Option<Uid>,
+    pub mine_only: bool,
+    pub container_filter: ContainerFilter,
+    pub show_io: bool,
+    /// Last cumulative (read, written) bytes per PID for I/O rates
+    pub io_history: HashMap<u32, (u64, u64, Instant)>,
+    pub show_fds: bool,
+    /// Include Unix domain socket listeners; finding their owners walks
+    /// every process's fd table, so this is opt-in
+    pub show_unix: bool,
+    /// Share of the soft fd limit at which the FDS column turns red
+    pub fd_warn_ratio: f64,
+    pub debug: bool,
+    pub worker: Worker,
+    /// Unfiltered rows of the last scan, kept so toggles can re-filter instantly
+    pub scanned: Vec<DevProcess>,
+    /// Wall time of the last scan
+    pub scan_cost: Duration,
+    /// PIDs the worker's sysinfo instance tracks
+    pub tracked: usize,
+    /// Generation of a scan requested with `r`, announced once it lands
+    pub announce_refresh: Option<u64>,
+    pub env_view: Option<EnvView>,
+    pub port_report: Option<PortReport>,
+    pub memory_metric: MemoryMetric,
+    pub cpu_mode: CpuMode,
+    /// Machine CPU load from the last scan, when the source reports it
+    pub machine_cpu: Option<MachineCpu>,
+    /// Side pane with the selected process's memory breakdown
+    pub show_memory_detail: bool,
+    pub show_help: bool,
+}
+
+/// Startup settings, mostly taken from the command line
+pub struct Options {
+    pub show_all: bool,
+    pub filter_port: Option<u16>,
+    pub filter_proto: Option<ProtoFilter>,
+    pub tick_rate_secs: u64,
+    pub probe_http: bool,
+    pub mine_only: bool,
+    pub show_io: bool,
+    pub show_fds: bool,
+    pub show_unix: bool,
+    pub fd_warn_ratio: f64,
+    pub debug: bool,
+    pub memory_metric: MemoryMetric,
+    pub cpu_mode: CpuMode,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            show_all: false,
+            filter_port: None,
+            filter_proto: None,
+            tick_rate_secs: 3,
+            probe_http: false,
+            mine_only: false,
+            show_io: false,
+            show_fds: false,
+            show_unix: false,
+            fd_warn_ratio: 0.8,
+            debug: false,
+            memory_metric: MemoryMetric::Rss,
+            cpu_mode: CpuMode::PerCore,
+        }
+    }
 }
 
 impl App {
-    pub fn new(show_all: bool, filter_port: Option<u16>, tick_rate_secs: u64) -> Self {
-        let mut system = System::new_all();
-        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
-        system.refresh_all();
+    pub fn new(options: Options, worker: Worker) -> Self {
+        let current_uid = worker.source().current_uid();
 
         let mut app = Self {
             running: true,
             processes: Vec::new(),
             selected: 0,
-            show_all,
+            show_all: options.show_all,
             sort_column: SortColumn::Port,
             sort_direction: SortDirection::Ascending,
             show_kill_confirm: false,
             kill_target: None,
             status_message: None,
             status_timer: 0,
-            system,
-            filter_port,
+            filter_port: options.filter_port,
+            filter_proto: options.filter_proto,
             last_refresh: Instant::now(),
             scrollbar_state: ScrollbarState::default(),
-            tick_rate_secs,
+            tick_rate_secs: options.tick_rate_secs,
             started_at: Instant::now(),
             owl_mood: OwlMood::Idle,
             owl_mood_until: Instant::now(),
             last_action: Instant::now(),
+            grouped: false,
+            expanded: HashSet::new(),
+            cmd_scroll: 0,
+            probe_http: options.probe_http,
+            tree_view: false,
+            current_uid,
+            mine_only: options.mine_only,
+            container_filter: ContainerFilter::Show,
+            show_io: options.show_io,
+            io_history: HashMap::new(),
+            show_fds: options.show_fds,
+            show_unix: options.show_unix || options.filter_proto == Some(ProtoFilter::Unix),
+            fd_warn_ratio: options.fd_warn_ratio,
+            debug: options.debug,
+            worker,
+            scanned: Vec::new(),
+            scan_cost: Duration::ZERO,
+            tracked: 0,
+            announce_refresh: None,
+            env_view: None,
+            port_report: None,
+            memory_metric: options.memory_metric,
+            cpu_mode: options.cpu_mode,
+            machine_cpu: None,
+            show_memory_detail: false,
+            show_help: false,
         };
         app.refresh();
         app
     }
 
+    pub fn input_mode(&self) -> InputMode {
+        if self.show_kill_confirm {
+            InputMode::Confirm
+        } else if self.show_help {
+            InputMode::Help
+        } else if self.port_report.is_some() {
+            InputMode::Inspect
+        } else {
+            match self.env_view {
+                Some(ref view) if view.searching => InputMode::EnvSearch,
+                Some(_) => InputMode::Env,
+                None => InputMode::Normal,
+            }
+        }
+    }
+
     pub fn set_owl_mood(&mut self, mood: OwlMood, duration_ms: u64) {
         self.owl_mood = mood;
         self.owl_mood_until = Instant::now() + std::time::Duration::from_millis(duration_ms);
         self.last_action = Instant::now();
     }
 
-    pub fn refresh(&mut self) {
-        self.system.refresh_all();
+    /// Asks the worker for a fresh scan; the result arrives as `Message::Scanned`.
+    /// Returns the request's generation, see `Worker::request`
+    pub fn refresh(&mut self) -> u64 {
+        self.worker.request(ScanRequest {
+            tree: self.tree_view,
+            probe_http: self.probe_http,
+            show_all: self.show_all,
+            unix: self.show_unix,
+            memory_detail: self.needs_memory_detail(),
+        })
+    }
 
-        let mut processes = match scanner::scan(&self.system) {
+    /// Whether anything on screen shows more than RSS
+    pub fn needs_memory_detail(&self) -> bool {
+        self.memory_metric != MemoryMetric::Rss || self.show_memory_detail
+    }
+
+    pub fn apply_scan(&mut self, output: ScanOutput) {
+        self.worker.finished();
+        self.scan_cost = output.cost;
+        self.tracked = output.tracked;
+        self.machine_cpu = output.machine_cpu;
+        let answers_refresh = self.announce_refresh.is_some_and(|g| output.generation >= g);
+        if answers_refresh {
+            self.announce_refresh = None;
+        }
+
+        let mut processes = match output.processes {
             Ok(p) => p,
             Err(e) => {
                 self.status_message = Some(e);
@@ -117,38 +416,100 @@ impl App {
                 return;
             }
         };
+        if answers_refresh {
+            self.status_message = Some("Refreshed".to_string());
+            self.status_timer = 3;
+        }
+
+        self.update_io_rates(&mut processes, Instant::now());
+        self.scanned = processes;
+        self.last_refresh = Instant::now();
+        self.rebuild();
+    }
+
+    /// Re-applies filters, grouping and sorting to the last scan without
+    /// waiting for a new one
+    pub fn rebuild(&mut self) {
+        let mut processes = self.scanned.clone();
+
+        if !self.show_unix {
+            processes.retain(|p| !p.is_unix());
+        }
+
+        if let Some(proto) = self.filter_proto {
+            processes.retain(|p| proto.matches(&p.protocol));
+        }
 
         if !self.show_all {
-            processes = filter::filter_dev(processes);
+            processes = self.worker.source().dev_filter().filter(processes);
+        }
+
+        if self.mine_only {
+            processes.retain(|p| self.is_mine(p));
+        }
+
+        match self.container_filter {
+            ContainerFilter::Show => {}
+            ContainerFilter::Hide => processes.retain(|p| p.container.is_none()),
+            ContainerFilter::Only => processes.retain(|p| p.container.is_some()),
+        }
+
+        if self.grouped {
+            processes = scanner::group_by_pid(processes);
+            self.expanded.retain(|pid| processes.iter().any(|p| p.pid == *pid));
         }
 
         if let Some(port) = self.filter_port {
-            processes.retain(|p| p.port == port);
+            processes.retain(|p| p.has_port(port));
         }
 
         self.sort(&mut processes);
+        if self.tree_view {
+            processes = tree::arrange(processes);
+        }
         self.processes = processes;
 
         if self.selected >= self.processes.len() && !self.processes.is_empty() {
             self.selected = self.processes.len() - 1;
         }
 
-        self.last_refresh = Instant::now();
         self.scrollbar_state = self.scrollbar_state
             .content_length(self.processes.len())
             .position(self.selected);
     }
 
+    /// Derives per-second I/O from the cumulative counters of the last refresh
+    fn update_io_rates(&mut self, processes: &mut [DevProcess], now: Instant) {
+        for p in processes.iter_mut() {
+            if let Some(&(read, written, at)) = self.io_history.get(&p.pid) {
+                let elapsed = now.duration_since(at);
+                p.io_read_rate = Some(scanner::rate_per_sec(read, p.io_read_total, elapsed));
+                p.io_write_rate = Some(scanner::rate_per_sec(written, p.io_write_total, elapsed));
+            }
+        }
+        self.io_history = processes
+            .iter()
+            .map(|p| (p.pid, (p.io_read_total, p.io_write_total, now)))
+            .collect();
+    }
+
     pub fn sort(&self, processes: &mut [DevProcess]) {
         let dir = self.sort_direction;
         processes.sort_by(|a, b| {
             let ord = match self.sort_column {
                 SortColumn::Pid => a.pid.cmp(&b.pid),
                 SortColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-                SortColumn::Port => a.port.cmp(&b.port),
+                SortColumn::Project => a
+                    .project
+                    .as_deref()
+                    .map(str::to_lowercase)
+                    .cmp(&b.project.as_deref().map(str::to_lowercase)),
+                SortColumn::Port => (a.is_unix(), a.port, &a.socket_path)
+                    .cmp(&(b.is_unix(), b.port, &b.socket_path)),
                 SortColumn::Proto => a.protocol.cmp(&b.protocol),
+                SortColumn::Conn => a.connections.cmp(&b.connections),
                 SortColumn::Cpu => a.cpu_percent.partial_cmp(&b.cpu_percent).unwrap_or(std::cmp::Ordering::Equal),
-                SortColumn::Memory => a.memory_bytes.cmp(&b.memory_bytes),
+                SortColumn::Memory => self.memory_metric.of(a).cmp(&self.memory_metric.of(b)),
                 SortColumn::Uptime => a.uptime_secs.cmp(&b.uptime_secs),
             };
             match dir {
@@ -158,11 +519,56 @@ impl App {
         });
     }
 
+    /// True when the process runs as the same user as srvtop. If our own UID
+    /// can't be determined every process counts as ours.
+    pub fn is_mine(&self, process: &DevProcess) -> bool {
+        match self.current_uid {
+            Some(ref me) => process.uid.as_ref() == Some(me),
+            None => true,
+        }
+    }
+
+    /// Name of the process owner when we likely lack permission to signal it
+    pub fn foreign_owner(&self, process: &DevProcess) -> Option<String> {
+        let privileged = self.current_uid.as_ref().is_some_and(scanner::is_privileged);
+        if privileged || self.is_mine(process) {
+            return None;
+        }
+        Some(process.user.clone().unwrap_or_else(|| "another user".to_string()))
+    }
+
+    /// Logical cores to normalise by; the scanner's count once it has one
+    pub fn cores(&self) -> usize {
+        match self.machine_cpu {
+            Some(cpu) => cpu.cores,
+            None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+
+    /// CPU% as shown in the current mode, and the value a full bar stands for.
+    /// The bar always shows a share of the machine, so 380% and 100% differ.
+    pub fn cpu_shown(&self, process: &DevProcess) -> (f32, f32) {
+        let cores = self.cores() as f32;
+        match self.cpu_mode {
+            CpuMode::PerCore => (process.cpu_percent, 100.0 * cores),
+            CpuMode::Machine => (process.cpu_percent / cores, 100.0),
+        }
+    }
+
     pub fn selected_process(&self) -> Option<&DevProcess> {
         self.processes.get(self.selected)
     }
 }
 
+/// An app over an empty inline worker showing `processes`, for tests that
+/// only need the view state
+#[cfg(test)]
+pub fn test_app(processes: Vec<DevProcess>) -> App {
+    let mut app = App::new(Options::default(), Worker::detached());
+    app.processes = processes;
+    app
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,29 +581,21 @@ mod tests {
         ]
     }
 
+    #[test]
+    fn cpu_normalises_by_core_count() {
+        let mut app = test_app(Vec::new());
+        app.machine_cpu = Some(MachineCpu { usage: 50.0, cores: 4 });
+        let mut java = DevProcess::test("java", 8080);
+        java.cpu_percent = 380.0;
+
+        assert_eq!(app.cpu_shown(&java), (380.0, 400.0));
+        app.cpu_mode = CpuMode::Machine;
+        assert_eq!(app.cpu_shown(&java), (95.0, 100.0));
+    }
+
     #[test]
     fn sort_by_port_ascending() {
-        let app = App {
-            running: true,
-            processes: Vec::new(),
-            selected: 0,
-            show_all: false,
-            sort_column: SortColumn::Port,
-            sort_direction: SortDirection::Ascending,
-            show_kill_confirm: false,
-            kill_target: None,
-            status_message: None,
-            status_timer: 0,
-            system: System::new(),
-            filter_port: None,
-            last_refresh: Instant::now(),
-            scrollbar_state: ScrollbarState::default(),
-            tick_rate_secs: 3,
-            started_at: Instant::now(),
-            owl_mood: OwlMood::Idle,
-            owl_mood_until: Instant::now(),
-            last_action: Instant::now(),
-        };
+        let app = test_app(Vec::new());
         let mut procs = make_processes();
         app.sort(&mut procs);
         assert_eq!(procs[0].port, 3000);
@@ -207,27 +605,8 @@ mod tests {
 
     #[test]
     fn sort_by_port_descending() {
-        let app = App {
-            running: true,
-            processes: Vec::new(),
-            selected: 0,
-            show_all: false,
-            sort_column: SortColumn::Port,
-            sort_direction: SortDirection::Descending,
-            show_kill_confirm: false,
-            kill_target: None,
-            status_message: None,
-            status_timer: 0,
-            system: System::new(),
-            filter_port: None,
-            last_refresh: Instant::now(),
-            scrollbar_state: ScrollbarState::default(),
-            tick_rate_secs: 3,
-            started_at: Instant::now(),
-            owl_mood: OwlMood::Idle,
-            owl_mood_until: Instant::now(),
-            last_action: Instant::now(),
-        };
+        let mut app = test_app(Vec::new());
+        app.sort_direction = SortDirection::Descending;
         let mut procs = make_processes();
         app.sort(&mut procs);
         assert_eq!(procs[0].port, 6379);
@@ -236,27 +615,8 @@ mod tests {
 
     #[test]
     fn sort_by_name() {
-        let app = App {
-            running: true,
-            processes: Vec::new(),
-            selected: 0,
-            show_all: false,
-            sort_column: SortColumn::Name,
-            sort_direction: SortDirection::Ascending,
-            show_kill_confirm: false,
-            kill_target: None,
-            status_message: None,
-            status_timer: 0,
-            system: System::new(),
-            filter_port: None,
-            last_refresh: Instant::now(),
-            scrollbar_state: ScrollbarState::default(),
-            tick_rate_secs: 3,
-            started_at: Instant::now(),
-            owl_mood: OwlMood::Idle,
-            owl_mood_until: Instant::now(),
-            last_action: Instant::now(),
-        };
+        let mut app = test_app(Vec::new());
+        app.sort_column = SortColumn::Name;
         let mut procs = make_processes();
         app.sort(&mut procs);
         assert_eq!(procs[0].name, "node");
@@ -265,28 +625,64 @@ mod tests {
     }
 
     #[test]
-    fn selected_process_empty() {
-        let app = App {
-            running: true,
-            processes: Vec::new(),
-            selected: 0,
-            show_all: false,
-            sort_column: SortColumn::Port,
-            sort_direction: SortDirection::Ascending,
-            show_kill_confirm: false,
-            kill_target: None,
-            status_message: None,
-            status_timer: 0,
-            system: System::new(),
-            filter_port: None,
-            last_refresh: Instant::now(),
-            scrollbar_state: ScrollbarState::default(),
-            tick_rate_secs: 3,
-            started_at: Instant::now(),
-            owl_mood: OwlMood::Idle,
-            owl_mood_until: Instant::now(),
-            last_action: Instant::now(),
+    fn proto_filter_matches_case_insensitively() {
+        assert!(ProtoFilter::Tcp.matches("TCP"));
+        assert!(ProtoFilter::Udp.matches("udp"));
+        assert!(!ProtoFilter::Udp.matches("TCP"));
+    }
+
+    #[test]
+    fn sort_by_project() {
+        let mut app = test_app(Vec::new());
+        app.sort_column = SortColumn::Project;
+        let mut procs = make_processes();
+        procs[0].project = Some("storefront".to_string());
+        procs[1].project = Some("Billing-api".to_string());
+        app.sort(&mut procs);
+        // Rows without a project sort first when ascending
+        assert_eq!(procs[0].name, "redis");
+        assert_eq!(procs[1].name, "postgres");
+        assert_eq!(procs[2].name, "node");
+
+        app.sort_direction = SortDirection::Descending;
+        app.sort(&mut procs);
+        assert_eq!(procs[0].name, "node");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn foreign_owner_flags_other_users() {
+        let mut app = test_app(Vec::new());
+        app.current_uid = Uid::try_from(1000usize).ok();
+        let mine = DevProcess {
+            uid: Uid::try_from(1000usize).ok(),
+            ..DevProcess::test("node", 3000)
+        };
+        let theirs = DevProcess {
+            uid: Uid::try_from(1001usize).ok(),
+            user: Some("alice".to_string()),
+            ..DevProcess::test("postgres", 5432)
         };
+        assert!(app.is_mine(&mine));
+        assert!(!app.is_mine(&theirs));
+        assert_eq!(app.foreign_owner(&mine), None);
+        assert_eq!(app.foreign_owner(&theirs), Some("alice".to_string()));
+
+        app.current_uid = Uid::try_from(0usize).ok();
+        assert_eq!(app.foreign_owner(&theirs), None);
+    }
+
+    #[test]
+    fn selected_process_empty() {
+        let app = test_app(Vec::new());
         assert!(app.selected_process().is_none());
     }
+
+    #[test]
+    fn selected_process_follows_selection() {
+        let mut app = test_app(make_processes());
+        assert_eq!(app.selected_process().map(|p| p.port), Some(3000));
+        app.selected = 1;
+        assert_eq!(app.selected_process().map(|p| p.port), Some(5432));
+    }
 }
@@ -0,0 +1,324 @@
+//! Config files that adjust DEV mode's built-in names and ports: one per
+//! user, plus an optional `.srvtop.toml` in a repo applied on top of it.
+
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::filter::DevFilter;
+
+/// File name looked for in the working directory and its parents
+pub const REPO_FILE: &str = ".srvtop.toml";
+
+/// Written by `srvtop config init`
+pub const TEMPLATE: &str = r#"# Adjusts which processes DEV mode shows. Entries are added to or removed
+# from srvtop's built-in lists; `a` shows everything regardless.
+#
+# A .srvtop.toml in a repo (or any parent directory) is applied on top of
+# the user config, so a repo can re-add what the user file removes.
+
+[names]
+# Process names, matched case-insensitively and with an extension (node.exe)
+# add = ["fooserve"]
+# remove = ["java"]
+add = []
+remove = []
+
+[patterns]
+# Globs over the process name: * matches any run of characters, ? one
+# add = ["*-dev-server"]
+add = []
+remove = []
+
+[ports]
+# Single ports or "start-end" ranges; removals win over additions
+# add = [7777, "9000-9010"]
+# remove = [9090]
+add = []
+remove = []
+"#;
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub names: Edits<String>,
+    pub patterns: Edits<String>,
+    pub ports: Edits<PortSpec>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Edits<T> {
+    pub add: Vec<T>,
+    pub remove: Vec<T>,
+}
+
+impl<T> Default for Edits<T> {
+    fn default() -> Self {
+        Self {
+            add: Vec::new(),
+            remove: Vec::new(),
+        }
+    }
+}
+
+impl<T> Edits<T> {
+    fn all(&self) -> impl Iterator<Item = &T> {
+        self.add.iter().chain(&self.remove)
+    }
+}
+
+/// `7777` or `"9000-9010"`
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum PortSpec {
+    Port(i64),
+    Range(String),
+}
+
+impl PortSpec {
+    /// Inclusive (start, end)
+    pub fn range(&self) -> Result<(u16, u16), String> {
+        match self {
+            PortSpec::Port(port) => {
+                let port = parse_port(&port.to_string())?;
+                Ok((port, port))
+            }
+            PortSpec::Range(range) => {
+                let (start, end) = match range.split_once('-') {
+                    Some((start, end)) => (parse_port(start)?, parse_port(end)?),
+                    None => {
+                        let port = parse_port(range)?;
+                        (port, port)
+                    }
+                };
+                if start > end {
+                    return Err(format!("`{}` ends before it starts", range));
+                }
+                Ok((start, end))
+            }
+        }
+    }
+}
+
+fn parse_port(s: &str) -> Result<u16, String> {
+    match s.trim().parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(format!("`{}` isn't a port (1-65535)", s.trim())),
+    }
+}
+
+/// Parses and validates one config file
+pub fn parse(contents: &str) -> Result<Config, String> {
+    let config: Config = toml::from_str(contents).map_err(|e| e.to_string())?;
+    for (section, edits) in [("names", &config.names), ("patterns", &config.patterns)] {
+        if edits.all().any(|entry| entry.trim().is_empty()) {
+            return Err(format!("[{}] has an empty entry", section));
+        }
+    }
+    for spec in config.ports.all() {
+        spec.range().map_err(|e| format!("[ports] {}", e))?;
+    }
+    Ok(config)
+}
+
+impl Config {
+    /// Additions first, so a removal always wins within one file
+    pub fn apply(&self, filter: &mut DevFilter) {
+        for name in &self.names.add {
+            let name = name.trim().to_lowercase();
+            if !filter.names.contains(&name) {
+                filter.names.push(name);
+            }
+        }
+        for name in &self.names.remove {
+            let name = name.trim().to_lowercase();
+            filter.names.retain(|n| *n != name);
+        }
+
+        // Patterns match case-insensitively, so they're compared that way too
+        for pattern in &self.patterns.add {
+            let pattern = pattern.trim().to_lowercase();
+            if !filter.patterns.iter().any(|p| p.to_lowercase() == pattern) {
+                filter.patterns.push(pattern);
+            }
+        }
+        let removed: Vec<String> = self.patterns.remove.iter().map(|p| p.trim().to_lowercase()).collect();
+        filter.patterns.retain(|p| !removed.contains(&p.to_lowercase()));
+
+        // `parse` rejected bad ranges already
+        for (start, end) in self.ports.add.iter().filter_map(|p| p.range().ok()) {
+            filter.ports.insert_range(start, end);
+        }
+        for (start, end) in self.ports.remove.iter().filter_map(|p| p.range().ok()) {
+            filter.ports.remove_range(start, end);
+        }
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "names +{}/-{}, patterns +{}/-{}, ports +{}/-{}",
+            self.names.add.len(),
+            self.names.remove.len(),
+            self.patterns.add.len(),
+            self.patterns.remove.len(),
+            self.ports.add.len(),
+            self.ports.remove.len()
+        )
+    }
+}
+
+/// `$XDG_CONFIG_HOME/srvtop/config.toml`, falling back to `~/.config`
+#[cfg(not(windows))]
+pub fn user_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("srvtop").join("config.toml"))
+}
+
+#[cfg(windows)]
+pub fn user_path() -> Option<PathBuf> {
+    let base = std::env::var_os("APPDATA")?;
+    Some(PathBuf::from(base).join("srvtop").join("config.toml"))
+}
+
+/// Nearest `.srvtop.toml` in `cwd` or one of its parents
+pub fn repo_path(cwd: &Path) -> Option<PathBuf> {
+    cwd.ancestors().map(|dir| dir.join(REPO_FILE)).find(|path| path.is_file())
+}
+
+/// Existing config files, in the order they apply
+pub fn files() -> Vec<PathBuf> {
+    let cwd = std::env::current_dir().ok();
+    user_path()
+        .filter(|path| path.is_file())
+        .into_iter()
+        .chain(cwd.as_deref().and_then(repo_path))
+        .collect()
+}
+
+fn read(path: &Path) -> Result<Config, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// The built-in lists with every config file applied
+pub fn load() -> Result<DevFilter, String> {
+    let mut filter = DevFilter::default();
+    for path in files() {
+        read(&path)?.apply(&mut filter);
+    }
+    Ok(filter)
+}
+
+/// Writes the template to the user config, or to `.srvtop.toml` in the
+/// working directory with `repo`. Returns where it went.
+pub fn init(repo: bool, force: bool) -> Result<PathBuf, String> {
+    let path = if repo {
+        std::env::current_dir().map_err(|e| e.to_string())?.join(REPO_FILE)
+    } else {
+        user_path().ok_or("Can't tell where the config goes: HOME isn't set")?
+    };
+    if path.exists() && !force {
+        return Err(format!("{} already exists; pass --force to overwrite it", path.display()));
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    fs::write(&path, TEMPLATE).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// One line per config file for `srvtop config check`, and whether all of
+/// them are valid
+pub fn check() -> (String, bool) {
+    let files = files();
+    let mut out = String::new();
+    if files.is_empty() {
+        let _ = writeln!(out, "No config files; using the built-in lists");
+        if let Some(path) = user_path() {
+            let _ = writeln!(out, "`srvtop config init` creates {}", path.display());
+        }
+        return (out, true);
+    }
+
+    let mut ok = true;
+    for path in files {
+        match read(&path) {
+            Ok(config) => {
+                let _ = writeln!(out, "ok     {} ({})", path.display(), config.summary());
+            }
+            Err(e) => {
+                ok = false;
+                let _ = writeln!(out, "error  {}", e);
+            }
+        }
+    }
+    (out, ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::DevProcess;
+
+    #[test]
+    fn template_is_valid() {
+        let config = parse(TEMPLATE).unwrap();
+        assert!(config.names.add.is_empty() && config.ports.remove.is_empty());
+    }
+
+    #[test]
+    fn adds_and_removes_names_and_ports() {
+        let config = parse(
+            r#"
+            [names]
+            add = ["FooServe"]
+            remove = ["java"]
+
+            [ports]
+            add = [7777, "9100-9110"]
+            remove = ["9105"]
+            "#,
+        )
+        .unwrap();
+        let mut filter = DevFilter::default();
+        config.apply(&mut filter);
+
+        assert!(filter.is_dev_relevant(&DevProcess::test("fooserve", 49152)));
+        assert!(!filter.is_dev_relevant(&DevProcess::test("java", 49152)));
+        assert!(filter.is_dev_relevant(&DevProcess::test("unknown", 7777)));
+        assert!(filter.is_dev_relevant(&DevProcess::test("unknown", 9110)));
+        assert!(!filter.is_dev_relevant(&DevProcess::test("unknown", 9105)));
+    }
+
+    #[test]
+    fn patterns_compare_case_insensitively() {
+        let mut filter = DevFilter::default();
+        parse("[patterns]\nadd = [\"Foo*\", \"*-Dev\"]").unwrap().apply(&mut filter);
+        parse("[patterns]\nadd = [\"FOO*\"]\nremove = [\"*-DEV\"]").unwrap().apply(&mut filter);
+        assert_eq!(filter.patterns, vec!["foo*"]);
+    }
+
+    #[test]
+    fn rejects_bad_entries() {
+        assert!(parse("[ports]\nadd = [70000]").unwrap_err().contains("70000"));
+        assert!(parse("[ports]\nadd = [\"9010-9000\"]").unwrap_err().contains("ends before"));
+        assert!(parse("[names]\nadd = [\" \"]").unwrap_err().contains("empty entry"));
+        assert!(parse("[name]\nadd = [\"x\"]").is_err());
+    }
+
+    #[test]
+    fn finds_repo_config_in_a_parent() {
+        let root = std::env::temp_dir().join(format!("srvtop-config-{}", std::process::id()));
+        let nested = root.join("api").join("src");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(REPO_FILE), "").unwrap();
+        assert_eq!(repo_path(&nested), Some(root.join(REPO_FILE)));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,187 @@
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Runtime {
+    Docker,
+    Containerd,
+    Podman,
+    CriO,
+    Kubernetes,
+    Lxc,
+}
+
+impl Runtime {
+    pub fn label(self) -> &'static str {
+        match self {
+            Runtime::Docker => "docker",
+            Runtime::Containerd => "containerd",
+            Runtime::Podman => "podman",
+            Runtime::CriO => "cri-o",
+            Runtime::Kubernetes => "k8s",
+            Runtime::Lxc => "lxc",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Container {
+    pub runtime: Runtime,
+    /// Full container ID, when the process belongs to one specific container.
+    /// Host-side helpers like `docker-proxy` serve several and have none.
+    pub id: Option<String>,
+    /// Filled in from the Docker Engine API when it is reachable
+    pub name: Option<String>,
+    pub image: Option<String>,
+}
+
+impl Container {
+    pub fn new(runtime: Runtime, id: Option<String>) -> Self {
+        Self {
+            runtime,
+            id,
+            name: None,
+            image: None,
+        }
+    }
+
+    pub fn short_id(&self) -> Option<&str> {
+        self.id.as_deref().map(|id| &id[..id.len().min(12)])
+    }
+}
+
+/// Works out whether a process is part of a container, either because it
+/// runs inside one (per its cgroup) or because it is a runtime helper that
+/// holds ports on a container's behalf.
+pub fn detect(pid: u32, name: &str, cmdline: &str) -> Option<Container> {
+    if name == "docker-proxy" {
+        return Some(Container::new(Runtime::Docker, None));
+    }
+    if name.starts_with("containerd-shim") {
+        let id = cmdline
+            .split_whitespace()
+            .skip_while(|arg| *arg != "-id")
+            .nth(1)
+            .map(str::to_string);
+        return Some(Container::new(Runtime::Containerd, id));
+    }
+    read_cgroup(pid).and_then(|contents| parse_cgroup(&contents))
+}
+
+#[cfg(target_os = "linux")]
+fn read_cgroup(pid: u32) -> Option<String> {
+    std::fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_cgroup(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Parses `/proc/<pid>/cgroup` (v1 or v2) for a container runtime and ID
+pub fn parse_cgroup(contents: &str) -> Option<Container> {
+    contents.lines().find_map(|line| {
+        // hierarchy-ID:controllers:path
+        let path = line.splitn(3, ':').nth(2)?;
+        parse_cgroup_path(path)
+    })
+}
+
+fn parse_cgroup_path(path: &str) -> Option<Container> {
+    let kubernetes = path.contains("kubepods");
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    for (i, segment) in segments.iter().enumerate().rev() {
+        let scope = segment.strip_suffix(".scope").unwrap_or(segment);
+        let prefixed = [
+            ("docker-", Runtime::Docker),
+            ("libpod-", Runtime::Podman),
+            ("cri-containerd-", Runtime::Containerd),
+            ("crio-", Runtime::CriO),
+        ];
+        for (prefix, runtime) in prefixed {
+            if let Some(id) = scope.strip_prefix(prefix).filter(|id| is_container_id(id)) {
+                let runtime = if kubernetes { Runtime::Kubernetes } else { runtime };
+                return Some(Container::new(runtime, Some(id.to_string())));
+            }
+        }
+
+        // cgroup v1 layouts: /docker/<id>, /kubepods/.../<id>, /lxc/<name>
+        let parent = i.checked_sub(1).map(|p| segments[p]);
+        if is_container_id(segment) {
+            let runtime = match parent {
+                _ if kubernetes => Some(Runtime::Kubernetes),
+                Some("docker") => Some(Runtime::Docker),
+                Some("libpod_parent") => Some(Runtime::Podman),
+                _ if segments.contains(&"docker") => Some(Runtime::Docker),
+                _ => None,
+            };
+            if let Some(runtime) = runtime {
+                return Some(Container::new(runtime, Some(segment.to_string())));
+            }
+        }
+        if parent == Some("lxc") || parent == Some("lxc.payload") {
+            return Some(Container::new(Runtime::Lxc, Some(segment.to_string())));
+        }
+    }
+
+    None
+}
+
+fn is_container_id(s: &str) -> bool {
+    s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "3f2a1b9c0d1e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f90";
+
+    #[test]
+    fn parses_docker_cgroup_v1() {
+        let cgroup = format!("12:pids:/docker/{}\n11:memory:/docker/{}\n", ID, ID);
+        let container = parse_cgroup(&cgroup).unwrap();
+        assert_eq!(container.runtime, Runtime::Docker);
+        assert_eq!(container.short_id(), Some("3f2a1b9c0d1e"));
+    }
+
+    #[test]
+    fn parses_docker_cgroup_v2() {
+        let cgroup = format!("0::/system.slice/docker-{}.scope\n", ID);
+        assert_eq!(parse_cgroup(&cgroup).unwrap().runtime, Runtime::Docker);
+    }
+
+    #[test]
+    fn parses_podman_and_kubernetes() {
+        let podman = format!("0::/user.slice/user-1000.slice/user@1000.service/user.slice/libpod-{}.scope/container", ID);
+        assert_eq!(parse_cgroup(&podman).unwrap().runtime, Runtime::Podman);
+
+        let k8s = format!(
+            "0::/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod1234.slice/cri-containerd-{}.scope",
+            ID
+        );
+        let container = parse_cgroup(&k8s).unwrap();
+        assert_eq!(container.runtime, Runtime::Kubernetes);
+        assert_eq!(container.id.as_deref(), Some(ID));
+    }
+
+    #[test]
+    fn host_cgroups_are_not_containers() {
+        assert_eq!(parse_cgroup("0::/user.slice/user-1000.slice/session-2.scope\n"), None);
+        assert_eq!(parse_cgroup("0::/system.slice/docker.service\n"), None);
+    }
+
+    #[test]
+    fn detects_runtime_helpers() {
+        let proxy = detect(0, "docker-proxy", "docker-proxy -proto tcp -host-port 5432").unwrap();
+        assert_eq!(proxy.runtime, Runtime::Docker);
+        assert_eq!(proxy.id, None);
+
+        let shim = detect(
+            0,
+            "containerd-shim-runc-v2",
+            &format!("containerd-shim-runc-v2 -namespace moby -id {} -address /run/containerd.sock", ID),
+        )
+        .unwrap();
+        assert_eq!(shim.runtime, Runtime::Containerd);
+        assert_eq!(shim.short_id(), Some("3f2a1b9c0d1e"));
+    }
+}
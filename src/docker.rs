@@ -0,0 +1,319 @@
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::container::Runtime;
+use crate::scanner::DevProcess;
+
+const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+const IO_TIMEOUT: Duration = Duration::from_secs(2);
+/// Grace period handed to `docker stop`; the call blocks until it elapses
+const STOP_TIMEOUT_SECS: u32 = 3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublishedPort {
+    pub public_port: u16,
+    pub private_port: u16,
+    pub protocol: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContainerInfo {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    pub ports: Vec<PublishedPort>,
+}
+
+/// Minimal Docker Engine API client speaking HTTP/1.0 over the unix socket
+pub struct Client {
+    socket: PathBuf,
+}
+
+impl Client {
+    pub fn new(socket: impl Into<PathBuf>) -> Self {
+        Self {
+            socket: socket.into(),
+        }
+    }
+
+    /// Uses `DOCKER_HOST` when it points at a unix socket, else the default
+    pub fn from_env() -> Self {
+        let socket = std::env::var("DOCKER_HOST")
+            .ok()
+            .and_then(|host| host.strip_prefix("unix://").map(str::to_string))
+            .unwrap_or_else(|| DEFAULT_SOCKET.to_string());
+        Self::new(socket)
+    }
+
+    pub fn containers(&self) -> Result<Vec<ContainerInfo>, String> {
+        let (status, body) = self.request("GET", "/containers/json")?;
+        if status != 200 {
+            return Err(format!("Docker API returned {}", status));
+        }
+        parse_containers(&body)
+    }
+
+    pub fn stop(&self, id: &str) -> Result<(), String> {
+        let path = format!("/containers/{}/stop?t={}", id, STOP_TIMEOUT_SECS);
+        self.expect_no_content(&path)
+    }
+
+    pub fn restart(&self, id: &str) -> Result<(), String> {
+        let path = format!("/containers/{}/restart?t={}", id, STOP_TIMEOUT_SECS);
+        self.expect_no_content(&path)
+    }
+
+    fn expect_no_content(&self, path: &str) -> Result<(), String> {
+        let (status, body) = self.request("POST", path)?;
+        match status {
+            // 304: already in the requested state
+            204 | 304 => Ok(()),
+            _ => Err(api_error(status, &body)),
+        }
+    }
+
+    #[cfg(unix)]
+    fn request(&self, method: &str, path: &str) -> Result<(u16, String), String> {
+        use std::os::unix::net::UnixStream;
+
+        let mut stream = UnixStream::connect(&self.socket)
+            .map_err(|e| format!("{}: {}", self.socket.display(), e))?;
+        let timeout = IO_TIMEOUT + Duration::from_secs(STOP_TIMEOUT_SECS.into());
+        stream.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+        stream.set_write_timeout(Some(IO_TIMEOUT)).map_err(|e| e.to_string())?;
+
+        write!(
+            stream,
+            "{} {} HTTP/1.0\r\nHost: docker\r\nUser-Agent: srvtop\r\nContent-Length: 0\r\n\r\n",
+            method, path
+        )
+        .map_err(|e| e.to_string())?;
+
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw).map_err(|e| e.to_string())?;
+        parse_response(&raw)
+    }
+
+    #[cfg(not(unix))]
+    fn request(&self, _method: &str, _path: &str) -> Result<(u16, String), String> {
+        Err(format!("{}: unix sockets are not supported on this platform", self.socket.display()))
+    }
+}
+
+fn api_error(status: u16, body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|v| v.get("message")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("Docker API returned {}", status))
+}
+
+/// Splits a raw response into status and body. Chunk sizes count bytes, so
+/// the body is only decoded as UTF-8 once it's reassembled.
+fn parse_response(raw: &[u8]) -> Result<(u16, String), String> {
+    let split = find(raw, b"\r\n\r\n").ok_or("Malformed response from Docker")?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let body = &raw[split + 4..];
+    let status = head
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or("Malformed status line from Docker")?;
+    let chunked = head.lines().any(|line| {
+        line.split_once(':').is_some_and(|(k, v)| {
+            k.trim().eq_ignore_ascii_case("transfer-encoding") && v.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+    let body = if chunked { dechunk(body) } else { body.to_vec() };
+    Ok((status, String::from_utf8_lossy(&body).into_owned()))
+}
+
+fn dechunk(mut body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    while let Some(line_end) = find(body, b"\r\n") {
+        // Sizes may carry `;extension`s
+        let size = std::str::from_utf8(&body[..line_end])
+            .ok()
+            .and_then(|line| usize::from_str_radix(line.split(';').next()?.trim(), 16).ok())
+            .unwrap_or(0);
+        let rest = &body[line_end + 2..];
+        if size == 0 || rest.len() < size {
+            break;
+        }
+        out.extend_from_slice(&rest[..size]);
+        body = rest[size..].strip_prefix(b"\r\n").unwrap_or(&rest[size..]);
+    }
+    out
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+pub fn parse_containers(body: &str) -> Result<Vec<ContainerInfo>, String> {
+    let value: Value = serde_json::from_str(body).map_err(|e| e.to_string())?;
+    let list = value.as_array().ok_or("Expected a list of containers")?;
+
+    Ok(list
+        .iter()
+        .map(|c| {
+            let str_field = |key: &str| c.get(key).and_then(Value::as_str).unwrap_or_default();
+            let name = c
+                .get("Names")
+                .and_then(Value::as_array)
+                .and_then(|names| names.first())
+                .and_then(Value::as_str)
+                .map(|n| n.trim_start_matches('/').to_string())
+                .unwrap_or_default();
+            let ports = c
+                .get("Ports")
+                .and_then(Value::as_array)
+                .map(|ports| {
+                    ports
+                        .iter()
+                        .filter_map(|p| {
+                            Some(PublishedPort {
+                                public_port: p.get("PublicPort")?.as_u64()? as u16,
+                                private_port: p.get("PrivatePort")?.as_u64()? as u16,
+                                protocol: p.get("Type")?.as_str()?.to_uppercase(),
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            ContainerInfo {
+                id: str_field("Id").to_string(),
+                name,
+                image: str_field("Image").to_string(),
+                ports,
+            }
+        })
+        .collect())
+}
+
+/// Resolves Docker-owned rows to their container: processes inside a
+/// container by ID, `docker-proxy` by the host port it publishes.
+pub fn annotate(processes: &mut [DevProcess], containers: &[ContainerInfo]) {
+    for p in processes.iter_mut() {
+        let Some(ref mut container) = p.container else {
+            continue;
+        };
+        if !matches!(container.runtime, Runtime::Docker | Runtime::Containerd) {
+            continue;
+        }
+        let info = match container.id {
+            Some(ref id) => containers.iter().find(|c| c.id == *id),
+            None => containers.iter().find(|c| {
+                c.ports
+                    .iter()
+                    .any(|port| port.public_port == p.port && port.protocol == p.protocol)
+            }),
+        };
+        if let Some(info) = info {
+            container.id = Some(info.id.clone());
+            container.name = Some(info.name.clone());
+            container.image = Some(info.image.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::Container;
+
+    const CONTAINERS: &str = r#"[
+        {
+            "Id": "8dfafdbc3a40e1f2a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f80912",
+            "Names": ["/billing-db"],
+            "Image": "postgres:16",
+            "Ports": [
+                {"IP": "0.0.0.0", "PrivatePort": 5432, "PublicPort": 15432, "Type": "tcp"},
+                {"PrivatePort": 8080, "Type": "tcp"}
+            ]
+        }
+    ]"#;
+
+    #[test]
+    fn parses_container_list() {
+        let containers = parse_containers(CONTAINERS).unwrap();
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].name, "billing-db");
+        assert_eq!(containers[0].image, "postgres:16");
+        assert_eq!(
+            containers[0].ports,
+            vec![PublishedPort {
+                public_port: 15432,
+                private_port: 5432,
+                protocol: "TCP".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_chunked_response() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n[{}]\r\n0\r\n\r\n";
+        assert_eq!(parse_response(raw).unwrap(), (200, "[{}]".to_string()));
+    }
+
+    #[test]
+    fn chunks_may_split_a_character() {
+        // "é" is 0xC3 0xA9, and the first chunk ends between the two bytes
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\n[\"\xc3\r\n3\r\n\xa9\"]\r\n0\r\n\r\n";
+        assert_eq!(parse_response(raw).unwrap(), (200, "[\"\u{e9}\"]".to_string()));
+    }
+
+    #[test]
+    fn annotates_docker_proxy_by_published_port() {
+        let containers = parse_containers(CONTAINERS).unwrap();
+        let mut rows = vec![DevProcess {
+            container: Some(Container::new(Runtime::Docker, None)),
+            ..DevProcess::test("docker-proxy", 15432)
+        }];
+        annotate(&mut rows, &containers);
+        let container = rows[0].container.as_ref().unwrap();
+        assert_eq!(container.name.as_deref(), Some("billing-db"));
+        assert_eq!(container.short_id(), Some("8dfafdbc3a40"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn talks_to_mock_socket() {
+        use std::io::BufRead;
+        use std::os::unix::net::UnixListener;
+
+        let path = std::env::temp_dir().join(format!("srvtop-docker-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for reply in [
+                format!("HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n{}", CONTAINERS),
+                "HTTP/1.0 204 No Content\r\n\r\n".to_string(),
+                "HTTP/1.0 404 Not Found\r\n\r\n{\"message\":\"No such container: nope\"}".to_string(),
+            ] {
+                let (mut conn, _) = listener.accept().unwrap();
+                let mut line = String::new();
+                std::io::BufReader::new(&conn).read_line(&mut line).unwrap();
+                requests.push(line.trim().to_string());
+                conn.write_all(reply.as_bytes()).unwrap();
+            }
+            requests
+        });
+
+        let client = Client::new(&path);
+        assert_eq!(client.containers().unwrap()[0].name, "billing-db");
+        assert_eq!(client.restart("8dfafdbc3a40"), Ok(()));
+        assert_eq!(client.stop("nope"), Err("No such container: nope".to_string()));
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0], "GET /containers/json HTTP/1.0");
+        assert_eq!(requests[1], "POST /containers/8dfafdbc3a40/restart?t=3 HTTP/1.0");
+        assert_eq!(requests[2], "POST /containers/nope/stop?t=3 HTTP/1.0");
+        let _ = std::fs::remove_file(&path);
+    }
+}
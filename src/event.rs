@@ -1,23 +1,31 @@
+use std::sync::mpsc::Receiver;
 use std::time::{Duration, Instant};
 
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 
-use crate::app::Message;
+use crate::app::{InputMode, Message};
 
 pub struct EventHandler {
     tick_rate: Duration,
     last_tick: Instant,
+    /// Messages posted from other threads, e.g. finished scans
+    posted: Receiver<Message>,
 }
 
 impl EventHandler {
-    pub fn new(tick_rate_secs: u64) -> Self {
+    pub fn new(tick_rate_secs: u64, posted: Receiver<Message>) -> Self {
         Self {
             tick_rate: Duration::from_secs(tick_rate_secs),
             last_tick: Instant::now(),
+            posted,
         }
     }
 
-    pub fn next(&mut self) -> Option<Message> {
+    pub fn next(&mut self, mode: InputMode) -> Option<Message> {
+        if let Ok(msg) = self.posted.try_recv() {
+            return Some(msg);
+        }
+
         let until_tick = self
             .tick_rate
             .saturating_sub(self.last_tick.elapsed());
@@ -26,7 +34,7 @@ impl EventHandler {
 
         if event::poll(timeout).ok()? {
             if let Event::Key(key) = event::read().ok()? {
-                return self.handle_key(key);
+                return self.handle_key(key, mode);
             }
         }
 
@@ -38,11 +46,71 @@ impl EventHandler {
         None
     }
 
-    fn handle_key(&self, key: KeyEvent) -> Option<Message> {
+    fn handle_key(&self, key: KeyEvent, mode: InputMode) -> Option<Message> {
         if key.kind != crossterm::event::KeyEventKind::Press {
             return None;
         }
 
+        if mode == InputMode::Confirm {
+            return match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Some(Message::Quit)
+                }
+                KeyCode::Char('y') => Some(Message::ConfirmKill),
+                KeyCode::Char('s') => Some(Message::StopContainer),
+                KeyCode::Char('r') => Some(Message::RestartContainer),
+                KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => Some(Message::CancelKill),
+                _ => None,
+            };
+        }
+
+        if mode == InputMode::EnvSearch {
+            return match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Some(Message::Quit)
+                }
+                KeyCode::Char(c) => Some(Message::EnvSearchInput(c)),
+                KeyCode::Backspace => Some(Message::EnvSearchBackspace),
+                KeyCode::Enter => Some(Message::EndEnvSearch),
+                KeyCode::Esc => Some(Message::CancelEnvSearch),
+                _ => None,
+            };
+        }
+
+        if mode == InputMode::Inspect {
+            return match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Some(Message::Quit)
+                }
+                KeyCode::Char('w') | KeyCode::Char('q') | KeyCode::Esc => Some(Message::CloseInspect),
+                _ => None,
+            };
+        }
+
+        if mode == InputMode::Help {
+            return match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Some(Message::Quit)
+                }
+                KeyCode::Char('?') | KeyCode::Char('q') | KeyCode::Esc => Some(Message::ToggleHelp),
+                _ => None,
+            };
+        }
+
+        if mode == InputMode::Env {
+            return match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Some(Message::Quit)
+                }
+                KeyCode::Up | KeyCode::Char('k') => Some(Message::NavigateUp),
+                KeyCode::Down | KeyCode::Char('j') => Some(Message::NavigateDown),
+                KeyCode::Char('/') => Some(Message::StartEnvSearch),
+                KeyCode::Char('v') => Some(Message::RevealSecrets),
+                KeyCode::Char('e') | KeyCode::Char('q') | KeyCode::Esc => Some(Message::CloseEnv),
+                _ => None,
+            };
+        }
+
         match key.code {
             KeyCode::Char('q') => Some(Message::Quit),
             KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
@@ -51,12 +119,30 @@ impl EventHandler {
             KeyCode::Up | KeyCode::Char('k') => Some(Message::NavigateUp),
             KeyCode::Down | KeyCode::Char('j') => Some(Message::NavigateDown),
             KeyCode::Char('x') => Some(Message::Kill),
-            KeyCode::Char('y') => Some(Message::ConfirmKill),
-            KeyCode::Char('n') | KeyCode::Esc => Some(Message::CancelKill),
+            KeyCode::Char('X') => Some(Message::KillTree),
             KeyCode::Char('r') => Some(Message::Refresh),
             KeyCode::Char('a') => Some(Message::ToggleAll),
             KeyCode::Char('s') => Some(Message::CycleSort),
             KeyCode::Char('S') => Some(Message::ToggleSortDirection),
+            KeyCode::Char('g') => Some(Message::ToggleGroup),
+            KeyCode::Char('u') => Some(Message::CycleProto),
+            KeyCode::Char('t') => Some(Message::ToggleTree),
+            KeyCode::Char('m') => Some(Message::ToggleMine),
+            KeyCode::Char('c') => Some(Message::CycleContainers),
+            KeyCode::Char('i') => Some(Message::ToggleIo),
+            KeyCode::Char('f') => Some(Message::ToggleFds),
+            KeyCode::Char('U') => Some(Message::ToggleUnix),
+            KeyCode::Char('e') => Some(Message::ShowEnv),
+            KeyCode::Char('w') => Some(Message::InspectPort),
+            KeyCode::Char('M') => Some(Message::CycleMemoryMetric),
+            KeyCode::Char('C') => Some(Message::ToggleCpuMode),
+            KeyCode::Char('d') => Some(Message::ToggleMemoryDetail),
+            KeyCode::Char('?') => Some(Message::ToggleHelp),
+            KeyCode::Char('z') => Some(Message::Suspend),
+            KeyCode::Char('Z') => Some(Message::Resume),
+            KeyCode::Left | KeyCode::Char('h') => Some(Message::ScrollLeft),
+            KeyCode::Right | KeyCode::Char('l') => Some(Message::ScrollRight),
+            KeyCode::Enter | KeyCode::Char(' ') => Some(Message::ToggleExpand),
             _ => None,
         }
     }
@@ -1,4 +1,4 @@
-use crate::scanner::DevProcess;
+use crate::scanner::{self, DevProcess};
 
 const DEV_PROCESS_NAMES: &[&str] = &[
     "node", "nodejs", "deno", "bun",
@@ -29,26 +29,153 @@ const DEV_PORTS: &[u16] = &[
     27017,
 ];
 
-pub fn is_dev_relevant(process: &DevProcess) -> bool {
-    let name_lower = process.name.to_lowercase();
+/// Unix sockets whose file name contains one of these belong to dev services
+/// even when the owning process has a generic name
+const DEV_SOCKET_MARKERS: &[&str] = &[
+    ".s.PGSQL.", "mysql", "redis", "docker.sock", "containerd.sock",
+    "php-fpm", "gunicorn", "uwsgi", "puma", "unicorn",
+];
+
+/// Databases and caches that speak their own protocol; an HTTP probe would
+/// only show up in their logs as a bad handshake
+const NON_HTTP_NAMES: &[&str] = &[
+    "postgres", "postgresql",
+    "redis-server", "redis",
+    "mongod", "mongos",
+    "mysqld", "mariadbd", "mariadb",
+    "memcached",
+];
+
+const NON_HTTP_PORTS: &[u16] = &[3306, 5432, 5433, 6379, 11211, 27017];
+
+/// Every TCP/UDP port as one bit, so ranges add and remove cheaply
+#[derive(Clone)]
+pub struct PortSet {
+    words: Vec<u64>,
+}
+
+impl PortSet {
+    fn new() -> Self {
+        Self { words: vec![0; 1024] }
+    }
+
+    pub fn insert_range(&mut self, start: u16, end: u16) {
+        for port in start..=end {
+            self.words[port as usize / 64] |= 1 << (port % 64);
+        }
+    }
+
+    pub fn remove_range(&mut self, start: u16, end: u16) {
+        for port in start..=end {
+            self.words[port as usize / 64] &= !(1 << (port % 64));
+        }
+    }
+
+    pub fn contains(&self, port: u16) -> bool {
+        self.words[port as usize / 64] & (1 << (port % 64)) != 0
+    }
+}
+
+/// What DEV mode shows: the built-in lists, adjusted by config files
+#[derive(Clone)]
+pub struct DevFilter {
+    /// Lowercase process names, also matched with an extension (`node.exe`)
+    pub names: Vec<String>,
+    /// Case-insensitive globs over the process name, `*` and `?` only
+    pub patterns: Vec<String>,
+    pub ports: PortSet,
+}
+
+impl Default for DevFilter {
+    fn default() -> Self {
+        let mut ports = PortSet::new();
+        for port in DEV_PORTS {
+            ports.insert_range(*port, *port);
+        }
+        Self {
+            names: DEV_PROCESS_NAMES.iter().map(|n| n.to_string()).collect(),
+            patterns: Vec::new(),
+            ports,
+        }
+    }
+}
 
-    for dev_name in DEV_PROCESS_NAMES {
-        if name_lower == *dev_name || name_lower.starts_with(&format!("{}.", dev_name)) {
+impl DevFilter {
+    /// Whether a process with this name is a dev tool, whatever it listens on
+    pub fn matches_name(&self, name: &str) -> bool {
+        let name_lower = name.to_lowercase();
+
+        for dev_name in &self.names {
+            if name_lower == *dev_name || name_lower.starts_with(&format!("{}.", dev_name)) {
+                return true;
+            }
+        }
+
+        self.patterns.iter().any(|p| glob_match(&p.to_lowercase(), &name_lower))
+    }
+
+    pub fn is_dev_relevant(&self, process: &DevProcess) -> bool {
+        if self.matches_name(&process.name) {
             return true;
         }
+
+        match process.socket_path {
+            Some(ref path) => {
+                let name = scanner::socket_name(path);
+                DEV_SOCKET_MARKERS.iter().any(|m| name.contains(m))
+            }
+            None => self.ports.contains(process.port),
+        }
     }
 
-    DEV_PORTS.contains(&process.port)
+    pub fn filter(&self, processes: Vec<DevProcess>) -> Vec<DevProcess> {
+        processes.into_iter().filter(|p| self.is_dev_relevant(p)).collect()
+    }
+}
+
+/// Whether an HTTP probe might get an answer, judged by well-known non-HTTP
+/// services' names and ports
+pub fn may_speak_http(process: &DevProcess) -> bool {
+    let name = process.name.to_lowercase();
+    let known = NON_HTTP_NAMES
+        .iter()
+        .any(|n| name == *n || name.starts_with(&format!("{}.", n)));
+    !known && !NON_HTTP_PORTS.contains(&process.port)
 }
 
-pub fn filter_dev(processes: Vec<DevProcess>) -> Vec<DevProcess> {
-    processes.into_iter().filter(is_dev_relevant).collect()
+/// `*` matches any run of characters, `?` exactly one
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was, and how much text it has swallowed so far
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, t));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn is_dev_relevant(process: &DevProcess) -> bool {
+        DevFilter::default().is_dev_relevant(process)
+    }
+
     #[test]
     fn matches_dev_process_name() {
         assert!(is_dev_relevant(&DevProcess::test("node", 12345)));
@@ -70,6 +197,22 @@ mod tests {
         assert!(is_dev_relevant(&DevProcess::test("unknown", 27017)));
     }
 
+    #[test]
+    fn matches_dev_socket_path() {
+        let mut docker = DevProcess::test("dockerd", 0);
+        docker.socket_path = Some("/run/docker.sock".to_string());
+        assert!(is_dev_relevant(&docker));
+
+        let mut session = DevProcess::test("systemd", 0);
+        session.socket_path = Some("/run/user/1000/bus".to_string());
+        assert!(!is_dev_relevant(&session));
+
+        // Only the file name counts, not the directories above it
+        let mut agent = DevProcess::test("agent", 0);
+        agent.socket_path = Some("/home/redis/.cache/agent.sock".to_string());
+        assert!(!is_dev_relevant(&agent));
+    }
+
     #[test]
     fn rejects_non_dev_process() {
         assert!(!is_dev_relevant(&DevProcess::test("svchost", 49152)));
@@ -83,7 +226,28 @@ mod tests {
             DevProcess::test("svchost", 49152),
             DevProcess::test("unknown", 8080),
         ];
-        let filtered = filter_dev(processes);
+        let filtered = DevFilter::default().filter(processes);
         assert_eq!(filtered.len(), 2);
     }
+
+    #[test]
+    fn skips_probing_databases() {
+        assert!(may_speak_http(&DevProcess::test("node", 3000)));
+        assert!(!may_speak_http(&DevProcess::test("postgres", 5432)));
+        assert!(!may_speak_http(&DevProcess::test("redis-server.exe", 16379)));
+        assert!(!may_speak_http(&DevProcess::test("docker-proxy", 27017)));
+    }
+
+    #[test]
+    fn matches_name_globs() {
+        assert!(glob_match("*-dev-server", "api-dev-server"));
+        assert!(glob_match("foo?erve", "fooserve"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("a*b*c", "axxbyy"));
+        assert!(!glob_match("foo", "fooserve"));
+
+        let mut filter = DevFilter::default();
+        filter.patterns.push("FOO*".to_string());
+        assert!(filter.is_dev_relevant(&DevProcess::test("fooserve", 49152)));
+    }
 }
@@ -0,0 +1,268 @@
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::time::Duration;
+
+use ratatui::style::Color;
+
+const PROBE_TIMEOUT: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Framework {
+    Next,
+    Nuxt,
+    Vite,
+    Astro,
+    Angular,
+    Remix,
+    SvelteKit,
+    Storybook,
+    Webpack,
+    Express,
+    Django,
+    Flask,
+    FastApi,
+    Gunicorn,
+    Rails,
+    Spring,
+    Phoenix,
+    Laravel,
+    AspNet,
+    Hugo,
+}
+
+/// Command-line fragments that identify a framework. More specific markers
+/// come first so e.g. `next dev` wins over a generic `webpack` in the argv.
+const CMDLINE_MARKERS: &[(&str, Framework)] = &[
+    ("next dev", Framework::Next),
+    ("next start", Framework::Next),
+    ("next-server", Framework::Next),
+    ("next/dist", Framework::Next),
+    ("nuxt dev", Framework::Nuxt),
+    ("nuxi", Framework::Nuxt),
+    ("astro dev", Framework::Astro),
+    ("ng serve", Framework::Angular),
+    ("remix dev", Framework::Remix),
+    ("svelte-kit", Framework::SvelteKit),
+    ("storybook", Framework::Storybook),
+    ("vite", Framework::Vite),
+    ("webpack-dev-server", Framework::Webpack),
+    ("webpack serve", Framework::Webpack),
+    ("manage.py runserver", Framework::Django),
+    ("flask run", Framework::Flask),
+    ("fastapi dev", Framework::FastApi),
+    ("fastapi run", Framework::FastApi),
+    ("uvicorn", Framework::FastApi),
+    ("gunicorn", Framework::Gunicorn),
+    ("rails s", Framework::Rails),
+    ("rails server", Framework::Rails),
+    ("spring-boot:run", Framework::Spring),
+    ("bootrun", Framework::Spring),
+    ("org.springframework.boot", Framework::Spring),
+    ("phx.server", Framework::Phoenix),
+    ("artisan serve", Framework::Laravel),
+    ("hugo server", Framework::Hugo),
+];
+
+impl Framework {
+    pub fn label(self) -> &'static str {
+        match self {
+            Framework::Next => "next",
+            Framework::Nuxt => "nuxt",
+            Framework::Vite => "vite",
+            Framework::Astro => "astro",
+            Framework::Angular => "angular",
+            Framework::Remix => "remix",
+            Framework::SvelteKit => "sveltekit",
+            Framework::Storybook => "storybook",
+            Framework::Webpack => "webpack",
+            Framework::Express => "express",
+            Framework::Django => "django",
+            Framework::Flask => "flask",
+            Framework::FastApi => "fastapi",
+            Framework::Gunicorn => "gunicorn",
+            Framework::Rails => "rails",
+            Framework::Spring => "spring",
+            Framework::Phoenix => "phoenix",
+            Framework::Laravel => "laravel",
+            Framework::AspNet => "asp.net",
+            Framework::Hugo => "hugo",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Framework::Next | Framework::Express => Color::White,
+            Framework::Nuxt | Framework::Django | Framework::Spring => Color::Green,
+            Framework::Vite | Framework::Astro | Framework::Hugo => Color::Magenta,
+            Framework::Angular | Framework::Rails | Framework::Laravel => Color::Red,
+            Framework::Remix | Framework::AspNet => Color::Blue,
+            Framework::SvelteKit | Framework::Storybook => Color::LightRed,
+            Framework::Webpack | Framework::Flask => Color::Cyan,
+            Framework::FastApi | Framework::Gunicorn => Color::LightGreen,
+            Framework::Phoenix => Color::LightMagenta,
+        }
+    }
+}
+
+pub fn from_cmdline(cmdline: &str) -> Option<Framework> {
+    let cmdline = cmdline.to_lowercase();
+    CMDLINE_MARKERS
+        .iter()
+        .find(|(marker, _)| contains_word(&cmdline, marker))
+        .map(|(_, framework)| *framework)
+}
+
+/// Identifies a framework from the `Server` and `X-Powered-By` response headers
+pub fn from_headers(server: Option<&str>, powered_by: Option<&str>) -> Option<Framework> {
+    if let Some(powered_by) = powered_by.map(str::to_lowercase) {
+        let framework = if powered_by.contains("next.js") {
+            Some(Framework::Next)
+        } else if powered_by.contains("nuxt") {
+            Some(Framework::Nuxt)
+        } else if powered_by.contains("express") {
+            Some(Framework::Express)
+        } else if powered_by.contains("asp.net") {
+            Some(Framework::AspNet)
+        } else if powered_by.contains("phoenix") {
+            Some(Framework::Phoenix)
+        } else {
+            None
+        };
+        if framework.is_some() {
+            return framework;
+        }
+    }
+
+    let server = server?.to_lowercase();
+    if server.starts_with("wsgiserver") {
+        Some(Framework::Django)
+    } else if server.starts_with("werkzeug") {
+        Some(Framework::Flask)
+    } else if server.starts_with("uvicorn") {
+        Some(Framework::FastApi)
+    } else if server.starts_with("gunicorn") {
+        Some(Framework::Gunicorn)
+    } else if server.starts_with("puma") || server.starts_with("webrick") {
+        Some(Framework::Rails)
+    } else if server.starts_with("kestrel") {
+        Some(Framework::AspNet)
+    } else if server.starts_with("cowboy") {
+        Some(Framework::Phoenix)
+    } else {
+        None
+    }
+}
+
+/// Sends a `HEAD /` to the listener and fingerprints the response headers.
+/// Wildcard binds are probed on loopback.
+pub fn probe(address: IpAddr, port: u16) -> Option<Framework> {
+    let address = if address.is_unspecified() {
+        match address {
+            IpAddr::V4(_) => IpAddr::from([127, 0, 0, 1]),
+            IpAddr::V6(_) => IpAddr::from([0, 0, 0, 0, 0, 0, 0, 1]),
+        }
+    } else {
+        address
+    };
+
+    let mut stream = TcpStream::connect_timeout(&SocketAddr::new(address, port), PROBE_TIMEOUT).ok()?;
+    stream.set_read_timeout(Some(PROBE_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(PROBE_TIMEOUT)).ok()?;
+    stream
+        .write_all(b"HEAD / HTTP/1.0\r\nHost: localhost\r\nUser-Agent: srvtop\r\n\r\n")
+        .ok()?;
+
+    let mut buf = [0u8; 4096];
+    let mut len = 0;
+    while len < buf.len() {
+        match stream.read(&mut buf[len..]) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => len += n,
+        }
+        if buf[..len].windows(4).any(|w| w == b"\r\n\r\n") {
+            break;
+        }
+    }
+
+    let response = String::from_utf8_lossy(&buf[..len]);
+    if !response.starts_with("HTTP/") {
+        return None;
+    }
+    let header = |name: &str| {
+        response.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    };
+    from_headers(header("Server"), header("X-Powered-By"))
+}
+
+/// Substring match that refuses to match inside a longer word, so `vite`
+/// doesn't match `invite` but does match `node_modules/.bin/vite`.
+fn contains_word(haystack: &str, needle: &str) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    haystack.match_indices(needle).any(|(start, _)| {
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[start + needle.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn detects_from_cmdline() {
+        assert_eq!(
+            from_cmdline("node /app/node_modules/.bin/next dev -p 3001"),
+            Some(Framework::Next)
+        );
+        assert_eq!(
+            from_cmdline("node /app/node_modules/.bin/vite --host"),
+            Some(Framework::Vite)
+        );
+        assert_eq!(
+            from_cmdline("python3 manage.py runserver 0.0.0.0:8000"),
+            Some(Framework::Django)
+        );
+        assert_eq!(from_cmdline("ruby bin/rails s -p 3000"), Some(Framework::Rails));
+        assert_eq!(from_cmdline("mvn spring-boot:run"), Some(Framework::Spring));
+    }
+
+    #[test]
+    fn cmdline_ignores_partial_words() {
+        assert_eq!(from_cmdline("node invite-service.js"), None);
+        assert_eq!(from_cmdline("node server.js"), None);
+    }
+
+    #[test]
+    fn detects_from_headers() {
+        assert_eq!(from_headers(None, Some("Next.js")), Some(Framework::Next));
+        assert_eq!(from_headers(Some("nginx"), Some("Express")), Some(Framework::Express));
+        assert_eq!(
+            from_headers(Some("WSGIServer/0.2 CPython/3.12.1"), None),
+            Some(Framework::Django)
+        );
+        assert_eq!(from_headers(Some("Werkzeug/3.0.1 Python/3.12"), None), Some(Framework::Flask));
+        assert_eq!(from_headers(Some("nginx/1.25"), None), None);
+    }
+
+    #[test]
+    fn probe_reads_response_headers() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut conn, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 512];
+            let _ = conn.read(&mut buf);
+            conn.write_all(b"HTTP/1.1 200 OK\r\nX-Powered-By: Express\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+        });
+
+        assert_eq!(probe("0.0.0.0".parse().unwrap(), port), Some(Framework::Express));
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,272 @@
+//! "Who holds this port": every TCP and UDP socket on a port, not just
+//! listeners, and why a new server can't bind it yet.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::net::SocketAddr;
+
+use crate::netstat::{self, TcpState};
+use crate::procfs;
+
+pub struct PortSocket {
+    /// "TCP" or "UDP"
+    pub protocol: &'static str,
+    pub state: TcpState,
+    pub local: SocketAddr,
+    pub remote: SocketAddr,
+    /// (pid, name) of the process holding the socket. `None` for TIME_WAIT
+    /// sockets, which only the kernel holds, and for other users' processes
+    /// when not running as root.
+    pub owner: Option<(u32, String)>,
+}
+
+impl PortSocket {
+    pub fn is_udp(&self) -> bool {
+        self.protocol == "UDP"
+    }
+
+    /// UDP has no states of its own; the kernel reports a bound socket as
+    /// CLOSE, which `ss` calls UNCONN
+    pub fn state_label(&self) -> &'static str {
+        match self.state {
+            TcpState::Close if self.is_udp() => "UNCONN",
+            state => state.label(),
+        }
+    }
+
+    fn owner_label(&self) -> String {
+        match self.owner {
+            Some((pid, ref name)) => format!("{} (PID {})", name, pid),
+            None if self.state == TcpState::TimeWait => "the kernel".to_string(),
+            None => "an unknown process".to_string(),
+        }
+    }
+}
+
+/// Reads the live socket tables; owners come from a walk of every fd table
+pub fn sockets_on(port: u16) -> Result<Vec<PortSocket>, String> {
+    let tcp = netstat::tcp_sockets().ok_or("Socket tables are only readable on Linux")?;
+    let udp = netstat::udp_sockets().unwrap_or_default();
+    let entries: Vec<_> = tcp
+        .into_iter()
+        .map(|e| ("TCP", e))
+        .chain(udp.into_iter().map(|e| ("UDP", e)))
+        .filter(|(_, e)| e.local.port() == port || e.remote.port() == port)
+        .collect();
+
+    let inodes: HashSet<u64> = entries.iter().map(|(_, e)| e.inode).filter(|i| *i != 0).collect();
+    let owners = procfs::socket_owners(&inodes);
+    Ok(entries
+        .into_iter()
+        .map(|(protocol, e)| PortSocket {
+            protocol,
+            state: e.state,
+            local: e.local,
+            remote: e.remote,
+            owner: owners
+                .get(&e.inode)
+                .map(|&pid| (pid, procfs::comm(pid).unwrap_or_else(|| "?".to_string()))),
+        })
+        .collect())
+}
+
+/// Sockets on one port plus the reasons it can't be bound
+pub struct PortReport {
+    pub port: u16,
+    /// Sockets bound to the port first, listeners on top; then peers
+    /// connecting to it
+    pub sockets: Vec<PortSocket>,
+    pub reasons: Vec<String>,
+}
+
+impl PortReport {
+    pub fn new(port: u16, mut sockets: Vec<PortSocket>) -> Self {
+        sockets.sort_by_key(|s| {
+            (
+                s.local.port() != port,
+                s.is_udp(),
+                s.state != TcpState::Listen,
+                s.state_label(),
+                s.local,
+                s.remote,
+            )
+        });
+        let reasons = explain(port, &sockets);
+        Self {
+            port,
+            sockets,
+            reasons,
+        }
+    }
+
+    /// Plain-text table for `srvtop inspect`
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        if self.sockets.is_empty() {
+            let _ = writeln!(out, "No TCP or UDP sockets on :{}", self.port);
+        } else {
+            let _ = writeln!(out, "{:<5} {:<12} {:<42} {:<42} OWNER", "PROTO", "STATE", "LOCAL", "REMOTE");
+            for s in &self.sockets {
+                let _ = writeln!(
+                    out,
+                    "{:<5} {:<12} {:<42} {:<42} {}",
+                    s.protocol,
+                    s.state_label(),
+                    s.local,
+                    s.remote,
+                    s.owner_label()
+                );
+            }
+        }
+        let _ = writeln!(out);
+        for reason in &self.reasons {
+            let _ = writeln!(out, "* {}", reason);
+        }
+        out
+    }
+}
+
+/// Why binding `port` would fail with EADDRINUSE right now, most blocking
+/// first. Only sockets bound to the port count; peers connecting to it don't.
+pub fn explain(port: u16, sockets: &[PortSocket]) -> Vec<String> {
+    let (udp, bound): (Vec<&PortSocket>, Vec<&PortSocket>) = sockets
+        .iter()
+        .filter(|s| s.local.port() == port)
+        .partition(|s| s.is_udp());
+    let mut reasons = Vec::new();
+
+    for s in bound.iter().filter(|s| s.state == TcpState::Listen) {
+        reasons.push(format!(
+            "{} is listening on {}; stop it to free the port",
+            s.owner_label(),
+            s.local
+        ));
+    }
+
+    // Connections still open on the port, grouped by whoever holds them
+    let mut holders: Vec<(String, usize, bool)> = Vec::new();
+    for s in bound
+        .iter()
+        .filter(|s| !matches!(s.state, TcpState::Listen | TcpState::TimeWait))
+    {
+        let owner = s.owner_label();
+        let close_wait = s.state == TcpState::CloseWait;
+        match holders.iter_mut().find(|(o, _, _)| *o == owner) {
+            Some(holder) => {
+                holder.1 += 1;
+                holder.2 |= close_wait;
+            }
+            None => holders.push((owner, 1, close_wait)),
+        }
+    }
+    for (owner, count, close_wait) in holders {
+        let mut reason = format!(
+            "{} still holds {} connection{} on :{}",
+            owner,
+            count,
+            if count == 1 { "" } else { "s" },
+            port
+        );
+        if close_wait {
+            reason.push_str("; CLOSE_WAIT means the peer hung up but the process never closed its end");
+        }
+        reasons.push(reason);
+    }
+
+    let time_wait = bound.iter().filter(|s| s.state == TcpState::TimeWait).count();
+    if time_wait > 0 {
+        reasons.push(format!(
+            "{} TIME_WAIT socket{} from closed connections linger for up to 60s. Servers that set \
+             SO_REUSEADDR (Node, Go, Python's http.server) can bind anyway; others get EADDRINUSE \
+             until they expire",
+            time_wait,
+            if time_wait == 1 { "" } else { "s" }
+        ));
+    }
+
+    // TCP and UDP ports are separate, so these only block UDP servers
+    for s in udp {
+        reasons.push(format!(
+            "{} has UDP {} bound; another UDP server can't bind it, a TCP server still can",
+            s.owner_label(),
+            s.local
+        ));
+    }
+
+    if reasons.is_empty() {
+        reasons.push(format!("Nothing is bound to :{}; it's free to use", port));
+    }
+    reasons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn socket(state: TcpState, local: &str, remote: &str, owner: Option<(u32, &str)>) -> PortSocket {
+        PortSocket {
+            protocol: "TCP",
+            state,
+            local: local.parse().unwrap(),
+            remote: remote.parse().unwrap(),
+            owner: owner.map(|(pid, name)| (pid, name.to_string())),
+        }
+    }
+
+    #[test]
+    fn listener_is_the_first_reason() {
+        let report = PortReport::new(
+            3000,
+            vec![
+                socket(TcpState::Established, "127.0.0.1:51000", "127.0.0.1:3000", Some((9, "curl"))),
+                socket(TcpState::Established, "127.0.0.1:3000", "127.0.0.1:51000", Some((42, "node"))),
+                socket(TcpState::Listen, "0.0.0.0:3000", "0.0.0.0:0", Some((42, "node"))),
+            ],
+        );
+        assert_eq!(report.sockets[0].state, TcpState::Listen);
+        assert_eq!(report.sockets[2].owner.as_ref().unwrap().1, "curl");
+        assert_eq!(
+            report.reasons,
+            vec![
+                "node (PID 42) is listening on 0.0.0.0:3000; stop it to free the port".to_string(),
+                "node (PID 42) still holds 1 connection on :3000".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn explains_lingering_sockets_after_a_kill() {
+        let sockets = vec![
+            socket(TcpState::TimeWait, "127.0.0.1:3000", "127.0.0.1:51000", None),
+            socket(TcpState::TimeWait, "127.0.0.1:3000", "127.0.0.1:51002", None),
+            socket(TcpState::CloseWait, "127.0.0.1:3000", "127.0.0.1:51004", Some((7, "worker"))),
+        ];
+        let reasons = explain(3000, &sockets);
+        assert_eq!(reasons.len(), 2);
+        assert!(reasons[0].starts_with("worker (PID 7) still holds 1 connection"));
+        assert!(reasons[0].contains("CLOSE_WAIT"));
+        assert!(reasons[1].starts_with("2 TIME_WAIT sockets"));
+    }
+
+    #[test]
+    fn udp_sockets_keep_the_port_busy_for_udp() {
+        let hmr = PortSocket {
+            protocol: "UDP",
+            ..socket(TcpState::Close, "0.0.0.0:5173", "0.0.0.0:0", Some((31, "node")))
+        };
+        assert_eq!(hmr.state_label(), "UNCONN");
+        let report = PortReport::new(5173, vec![hmr]);
+        assert_eq!(
+            report.reasons,
+            vec!["node (PID 31) has UDP 0.0.0.0:5173 bound; another UDP server can't bind it, a TCP server still can"]
+        );
+        assert!(report.render().starts_with("PROTO"));
+    }
+
+    #[test]
+    fn peers_alone_leave_the_port_free() {
+        let sockets = vec![socket(TcpState::Established, "10.0.0.2:40000", "10.0.0.9:3000", Some((9, "curl")))];
+        assert_eq!(explain(3000, &sockets), vec!["Nothing is bound to :3000; it's free to use"]);
+        assert!(PortReport::new(3000, sockets).render().contains("curl (PID 9)"));
+    }
+}
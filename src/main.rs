@@ -1,21 +1,41 @@
 mod app;
+mod config;
+mod container;
+mod docker;
+mod environ;
 mod event;
 mod filter;
+mod framework;
+mod inspect;
+mod netstat;
+mod procfs;
+mod project;
 mod scanner;
+mod source;
+mod tree;
 mod ui;
 mod update;
+mod worker;
 
 use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::mpsc;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use color_eyre::eyre::eyre;
 use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::prelude::*;
 
-use app::App;
+use app::{App, CpuMode, MemoryMetric, Options, ProtoFilter};
 use event::EventHandler;
+use filter::DevFilter;
+use inspect::PortReport;
+use source::{FakeSource, LiveSource, ProcessSource};
+use worker::Worker;
 
 #[derive(Parser)]
 #[command(name = "srvtop", version, about = "Like htop, but for your dev servers")]
@@ -31,12 +51,147 @@ struct Cli {
     /// Filter to a specific port
     #[arg(short, long)]
     port: Option<u16>,
+
+    /// Only show listeners using this protocol
+    #[arg(long, value_enum)]
+    proto: Option<ProtoFilter>,
+
+    /// Probe HTTP listeners for Server/X-Powered-By headers to identify frameworks
+    #[arg(long)]
+    probe: bool,
+
+    /// Only show processes owned by the current user
+    #[arg(long)]
+    mine: bool,
+
+    /// Show disk read/write rate columns
+    #[arg(long)]
+    io: bool,
+
+    /// Include Unix domain socket listeners
+    #[arg(long)]
+    unix: bool,
+
+    /// Show thread and open file descriptor columns
+    #[arg(long)]
+    fds: bool,
+
+    /// Highlight FDS when usage passes this share of the soft limit
+    #[arg(long, default_value_t = 0.8, value_parser = parse_ratio)]
+    fd_warn: f64,
+
+    /// What 100% means in the CPU% column: one core or the whole machine
+    #[arg(long, value_enum, default_value_t = CpuMode::PerCore)]
+    cpu: CpuMode,
+
+    /// Memory figure for the MEMORY column and its bar
+    #[arg(long, value_enum, default_value_t = MemoryMetric::Rss)]
+    mem: MemoryMetric,
+
+    /// Show how long each scan takes in the header
+    #[arg(long)]
+    debug: bool,
+
+    /// Show processes from a JSON fixture instead of this machine (demos)
+    #[arg(long, value_name = "FILE")]
+    fixture: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List every TCP and UDP socket on a port, in any state, and explain why it
+    /// can't be bound yet
+    Inspect {
+        port: u16,
+    },
+    /// Create or validate the config file that adjusts DEV mode's names and ports
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Write a commented config to ~/.config/srvtop/config.toml
+    Init {
+        /// Write .srvtop.toml in the current directory instead
+        #[arg(long)]
+        repo: bool,
+        /// Overwrite an existing file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Parse every config file that applies here and report problems
+    Check,
+}
+
+fn parse_ratio(s: &str) -> Result<f64, String> {
+    let ratio: f64 = s.parse().map_err(|_| format!("`{}` isn't a number", s))?;
+    if ratio > 0.0 && ratio <= 1.0 {
+        Ok(ratio)
+    } else {
+        Err("must be greater than 0 and at most 1".to_string())
+    }
 }
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
     let cli = Cli::parse();
 
+    if let Some(Command::Config { action }) = cli.command {
+        match action {
+            ConfigAction::Init { repo, force } => {
+                let path = config::init(repo, force).map_err(|e| eyre!(e))?;
+                println!("Wrote {}", path.display());
+            }
+            ConfigAction::Check => {
+                let (report, ok) = config::check();
+                print!("{}", report);
+                if !ok {
+                    std::process::exit(1);
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    // Only the TUI filters rows, so a broken config can't stop `inspect`, and
+    // the TUI falls back to the built-in lists rather than refusing to start
+    let (dev_filter, config_error) = match cli.command {
+        Some(Command::Inspect { .. }) => (DevFilter::default(), None),
+        _ => match config::load() {
+            Ok(filter) => (filter, None),
+            Err(e) => (DevFilter::default(), Some(e)),
+        },
+    };
+
+    let source: Arc<dyn ProcessSource> = match cli.fixture {
+        Some(ref path) => Arc::new(
+            FakeSource::load(path)
+                .map_err(|e| eyre!(e))?
+                .with_dev_filter(dev_filter),
+        ),
+        None => Arc::new(LiveSource::new(dev_filter)),
+    };
+
+    if let Some(Command::Inspect { port }) = cli.command {
+        let sockets = source.port_sockets(port).map_err(|e| eyre!(e))?;
+        print!("{}", PortReport::new(port, sockets).render());
+        return Ok(());
+    }
+
+    let (posted_tx, posted_rx) = mpsc::channel();
+    // Fixtures answer instantly, so they don't need the scanning thread
+    let worker = if cli.fixture.is_some() {
+        Worker::inline(source, posted_tx)
+    } else {
+        Worker::spawn(source, posted_tx)
+    };
+
     // Restore terminal on panic
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
@@ -53,8 +208,29 @@ fn main() -> color_eyre::Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // App + event loop
-    let mut app = App::new(cli.all, cli.port, cli.interval);
-    let mut events = EventHandler::new(cli.interval);
+    let mut app = App::new(Options {
+        show_all: cli.all,
+        filter_port: cli.port,
+        filter_proto: cli.proto,
+        tick_rate_secs: cli.interval,
+        probe_http: cli.probe,
+        mine_only: cli.mine,
+        show_io: cli.io,
+        show_fds: cli.fds,
+        show_unix: cli.unix,
+        fd_warn_ratio: cli.fd_warn,
+        debug: cli.debug,
+        memory_metric: cli.mem,
+        cpu_mode: cli.cpu,
+    }, worker);
+    if let Some(e) = config_error {
+        app.status_message = Some(format!(
+            "Config ignored ({}); run `srvtop config check`",
+            e.lines().next().unwrap_or_default()
+        ));
+        app.status_timer = 5;
+    }
+    let mut events = EventHandler::new(cli.interval, posted_rx);
 
     while app.running {
         // Render
@@ -63,7 +239,7 @@ fn main() -> color_eyre::Result<()> {
         })?;
 
         // Handle events
-        if let Some(msg) = events.next() {
+        if let Some(msg) = events.next(app.input_mode()) {
             update::update(&mut app, msg);
         }
     }
@@ -0,0 +1,538 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TcpState {
+    Established,
+    SynSent,
+    SynRecv,
+    FinWait1,
+    FinWait2,
+    TimeWait,
+    Close,
+    CloseWait,
+    LastAck,
+    Listen,
+    Closing,
+    Unknown(u8),
+}
+
+impl TcpState {
+    fn from_code(code: u8) -> Self {
+        // include/net/tcp_states.h
+        match code {
+            0x01 => TcpState::Established,
+            0x02 => TcpState::SynSent,
+            0x03 => TcpState::SynRecv,
+            0x04 => TcpState::FinWait1,
+            0x05 => TcpState::FinWait2,
+            0x06 => TcpState::TimeWait,
+            0x07 => TcpState::Close,
+            0x08 => TcpState::CloseWait,
+            0x09 => TcpState::LastAck,
+            0x0A => TcpState::Listen,
+            0x0B => TcpState::Closing,
+            other => TcpState::Unknown(other),
+        }
+    }
+
+    /// Name as printed by `ss` and `netstat`
+    pub fn label(self) -> &'static str {
+        match self {
+            TcpState::Established => "ESTABLISHED",
+            TcpState::SynSent => "SYN_SENT",
+            TcpState::SynRecv => "SYN_RECV",
+            TcpState::FinWait1 => "FIN_WAIT1",
+            TcpState::FinWait2 => "FIN_WAIT2",
+            TcpState::TimeWait => "TIME_WAIT",
+            TcpState::Close => "CLOSE",
+            TcpState::CloseWait => "CLOSE_WAIT",
+            TcpState::LastAck => "LAST_ACK",
+            TcpState::Listen => "LISTEN",
+            TcpState::Closing => "CLOSING",
+            TcpState::Unknown(_) => "UNKNOWN",
+        }
+    }
+}
+
+/// One row of `/proc/net/tcp`, `/proc/net/udp` or their v6 twins
+#[derive(Clone, Debug, PartialEq)]
+pub struct SocketEntry {
+    pub local: SocketAddr,
+    pub remote: SocketAddr,
+    pub state: TcpState,
+    pub tx_queue: u32,
+    pub rx_queue: u32,
+    pub uid: u32,
+    pub inode: u64,
+}
+
+/// Every TCP socket on the machine. Only Linux exposes these tables;
+/// elsewhere this is empty and callers treat the data as unavailable.
+#[cfg(target_os = "linux")]
+pub fn tcp_sockets() -> Option<Vec<SocketEntry>> {
+    let v4 = std::fs::read_to_string("/proc/net/tcp").ok()?;
+    let mut entries = parse_table(&v4);
+    if let Ok(v6) = std::fs::read_to_string("/proc/net/tcp6") {
+        entries.extend(parse_table(&v6));
+    }
+    Some(entries)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn tcp_sockets() -> Option<Vec<SocketEntry>> {
+    None
+}
+
+/// Every UDP socket on the machine, from tables in the same format. Bound
+/// but unconnected sockets show up as `TcpState::Close`.
+#[cfg(target_os = "linux")]
+pub fn udp_sockets() -> Option<Vec<SocketEntry>> {
+    let v4 = std::fs::read_to_string("/proc/net/udp").ok()?;
+    let mut entries = parse_table(&v4);
+    if let Ok(v6) = std::fs::read_to_string("/proc/net/udp6") {
+        entries.extend(parse_table(&v6));
+    }
+    Some(entries)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn udp_sockets() -> Option<Vec<SocketEntry>> {
+    None
+}
+
+pub fn parse_table(contents: &str) -> Vec<SocketEntry> {
+    contents.lines().skip(1).filter_map(parse_line).collect()
+}
+
+fn parse_line(line: &str) -> Option<SocketEntry> {
+    let mut fields = line.split_whitespace();
+    let local = parse_socket_addr(fields.nth(1)?)?;
+    let remote = parse_socket_addr(fields.next()?)?;
+    let state = TcpState::from_code(u8::from_str_radix(fields.next()?, 16).ok()?);
+    let (tx_queue, rx_queue) = fields.next()?.split_once(':')?;
+    let uid = fields.nth(2)?.parse().ok()?;
+    let inode = fields.nth(1)?.parse().ok()?;
+
+    Some(SocketEntry {
+        local,
+        remote,
+        state,
+        tx_queue: u32::from_str_radix(tx_queue, 16).ok()?,
+        rx_queue: u32::from_str_radix(rx_queue, 16).ok()?,
+        uid,
+        inode,
+    })
+}
+
+/// Parses `0100007F:0BB8`. The kernel prints each 32-bit word of the address
+/// in host byte order, so the bytes come back out with `to_ne_bytes`.
+fn parse_socket_addr(field: &str) -> Option<SocketAddr> {
+    let (addr, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+
+    let ip = match addr.len() {
+        8 => IpAddr::V4(Ipv4Addr::from(u32::from_str_radix(addr, 16).ok()?.to_ne_bytes())),
+        32 => {
+            let mut bytes = [0u8; 16];
+            for (i, chunk) in bytes.chunks_mut(4).enumerate() {
+                let word = u32::from_str_radix(&addr[i * 8..i * 8 + 8], 16).ok()?;
+                chunk.copy_from_slice(&word.to_ne_bytes());
+            }
+            IpAddr::V6(Ipv6Addr::from(bytes))
+        }
+        _ => return None,
+    };
+
+    Some(SocketAddr::new(ip, port))
+}
+
+/// Number of ESTABLISHED connections accepted by each listening socket, keyed
+/// by its address. A connection belongs to the listener bound to its exact
+/// local address, else to a wildcard (`0.0.0.0`, `::`) listener of the same
+/// family on its port. Sockets with no such listener, like outgoing client
+/// connections, aren't counted.
+pub fn established_counts(entries: &[SocketEntry]) -> HashMap<SocketAddr, u32> {
+    let listeners: Vec<SocketAddr> = entries
+        .iter()
+        .filter(|e| e.state == TcpState::Listen)
+        .map(|e| e.local)
+        .collect();
+
+    let mut counts = HashMap::new();
+    for entry in entries.iter().filter(|e| e.state == TcpState::Established) {
+        let local = entry.local;
+        let on_port = || listeners.iter().filter(|l| l.port() == local.port());
+        let listener = on_port().find(|l| l.ip() == local.ip()).or_else(|| {
+            on_port().find(|l| l.ip().is_unspecified() && l.is_ipv4() == local.is_ipv4())
+        });
+        if let Some(listener) = listener {
+            *counts.entry(*listener).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// A listening Unix domain socket from `/proc/net/unix`
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnixListener {
+    pub inode: u64,
+    /// Filesystem path, or `@name` for the abstract namespace
+    pub path: String,
+}
+
+/// `__SO_ACCEPTCON`: the socket has called `listen()`
+const UNIX_ACCEPTCON: u32 = 0x0001_0000;
+
+#[cfg(target_os = "linux")]
+pub fn unix_listeners() -> Option<Vec<UnixListener>> {
+    Some(parse_unix_table(&std::fs::read_to_string("/proc/net/unix").ok()?))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn unix_listeners() -> Option<Vec<UnixListener>> {
+    None
+}
+
+/// Keeps named sockets in the listening state; unnamed ones can't be
+/// connected to, so there's nothing to show for them
+pub fn parse_unix_table(contents: &str) -> Vec<UnixListener> {
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let flags = u32::from_str_radix(fields.nth(3)?, 16).ok()?;
+            let inode = fields.nth(2)?.parse().ok()?;
+            let path = fields.collect::<Vec<_>>().join(" ");
+            (flags & UNIX_ACCEPTCON != 0 && !path.is_empty()).then_some(UnixListener { inode, path })
+        })
+        .collect()
+}
+
+/// Accept-queue fill of a listening TCP socket
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AcceptQueue {
+    /// Connections the kernel completed that the server hasn't `accept()`ed
+    pub pending: u32,
+    /// Backlog given to `listen()`, capped by `net.core.somaxconn`
+    pub backlog: u32,
+}
+
+impl AcceptQueue {
+    pub fn fill(&self) -> f64 {
+        if self.backlog == 0 {
+            return 0.0;
+        }
+        self.pending as f64 / self.backlog as f64
+    }
+
+    /// Fuller than `other`, used to pick one queue per port or process
+    pub fn fuller(self, other: AcceptQueue) -> AcceptQueue {
+        if other.fill() > self.fill() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+/// Accept queues of every listening TCP socket by address. The backlog limit
+/// isn't in `/proc/net/tcp`, so this asks sock_diag over netlink, as `ss` does.
+/// When an address has several sockets (`SO_REUSEPORT`) the fullest one wins.
+#[cfg(target_os = "linux")]
+pub fn accept_queues() -> Option<HashMap<SocketAddr, AcceptQueue>> {
+    let mut listeners = diag::dump_listeners(libc::AF_INET as u8).ok()?;
+    // IPv6 may be disabled; v4 alone is still useful
+    if let Ok(v6) = diag::dump_listeners(libc::AF_INET6 as u8) {
+        listeners.extend(v6);
+    }
+
+    let mut queues = HashMap::new();
+    for (addr, queue) in listeners {
+        queues
+            .entry(addr)
+            .and_modify(|q: &mut AcceptQueue| *q = q.fuller(queue))
+            .or_insert(queue);
+    }
+    Some(queues)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn accept_queues() -> Option<HashMap<SocketAddr, AcceptQueue>> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+mod diag {
+    use std::io;
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    use super::AcceptQueue;
+
+    // linux/netlink.h, linux/sock_diag.h and linux/inet_diag.h
+    const NLMSG_ERROR: u16 = 2;
+    const NLMSG_DONE: u16 = 3;
+    const SOCK_DIAG_BY_FAMILY: u16 = 20;
+    const TCP_LISTEN: u32 = 10;
+    const HEADER_LEN: usize = 16;
+    const REQUEST_LEN: usize = HEADER_LEN + 56;
+
+    /// (address, queue) for each LISTEN socket of `family`
+    pub fn dump_listeners(family: u8) -> io::Result<Vec<(SocketAddr, AcceptQueue)>> {
+        // SAFETY: plain socket(2) call; the fd is owned right after
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+                libc::NETLINK_SOCK_DIAG,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fd` is a fresh descriptor nobody else owns
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        // SAFETY: sockaddr_nl is plain data; all-zero means "the kernel"
+        let mut kernel: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+        kernel.nl_family = libc::AF_NETLINK as u16;
+        let req = request(family);
+        // SAFETY: both pointers are valid for the lengths passed
+        let sent = unsafe {
+            libc::sendto(
+                fd.as_raw_fd(),
+                req.as_ptr().cast(),
+                req.len(),
+                0,
+                (&kernel as *const libc::sockaddr_nl).cast(),
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            )
+        };
+        if sent < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut buf = vec![0u8; 32 * 1024];
+        let mut out = Vec::new();
+        loop {
+            // SAFETY: `buf` is valid for writes of its full length
+            let n = unsafe { libc::recv(fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len(), 0) };
+            if n < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if n == 0 || parse_messages(&buf[..n as usize], &mut out)? {
+                return Ok(out);
+            }
+        }
+    }
+
+    /// A dump request for TCP sockets in LISTEN state
+    fn request(family: u8) -> [u8; REQUEST_LEN] {
+        let mut req = [0u8; REQUEST_LEN];
+        req[0..4].copy_from_slice(&(REQUEST_LEN as u32).to_ne_bytes());
+        req[4..6].copy_from_slice(&SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+        req[6..8].copy_from_slice(&((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16).to_ne_bytes());
+        req[HEADER_LEN] = family;
+        req[HEADER_LEN + 1] = libc::IPPROTO_TCP as u8;
+        req[HEADER_LEN + 4..HEADER_LEN + 8].copy_from_slice(&(1u32 << TCP_LISTEN).to_ne_bytes());
+        req
+    }
+
+    /// Walks one datagram of netlink messages. Returns true once the dump is done.
+    pub(super) fn parse_messages(mut buf: &[u8], out: &mut Vec<(SocketAddr, AcceptQueue)>) -> io::Result<bool> {
+        while buf.len() >= HEADER_LEN {
+            let len = u32::from_ne_bytes(buf[0..4].try_into().unwrap()) as usize;
+            let kind = u16::from_ne_bytes(buf[4..6].try_into().unwrap());
+            if len < HEADER_LEN || len > buf.len() {
+                break;
+            }
+            match kind {
+                NLMSG_DONE => return Ok(true),
+                NLMSG_ERROR => {
+                    let errno = buf
+                        .get(HEADER_LEN..HEADER_LEN + 4)
+                        .map_or(0, |b| i32::from_ne_bytes(b.try_into().unwrap()));
+                    return Err(io::Error::from_raw_os_error(-errno));
+                }
+                SOCK_DIAG_BY_FAMILY => out.extend(parse_diag_msg(&buf[HEADER_LEN..len])),
+                _ => {}
+            }
+            let aligned = (len + 3) & !3;
+            buf = &buf[aligned.min(buf.len())..];
+        }
+        Ok(false)
+    }
+
+    /// Reads an `inet_diag_msg`: the family is its first byte, the source
+    /// port and address sit in network order at offsets 4 and 8, the queues
+    /// (host order) at 56 and 60
+    fn parse_diag_msg(msg: &[u8]) -> Option<(SocketAddr, AcceptQueue)> {
+        let port = u16::from_be_bytes(msg.get(4..6)?.try_into().ok()?);
+        let ip = match i32::from(*msg.first()?) {
+            libc::AF_INET => Ipv4Addr::from(<[u8; 4]>::try_from(msg.get(8..12)?).ok()?).into(),
+            libc::AF_INET6 => Ipv6Addr::from(<[u8; 16]>::try_from(msg.get(8..24)?).ok()?).into(),
+            _ => return None,
+        };
+        let pending = u32::from_ne_bytes(msg.get(56..60)?.try_into().ok()?);
+        let backlog = u32::from_ne_bytes(msg.get(60..64)?.try_into().ok()?);
+        Some((SocketAddr::new(ip, port), AcceptQueue { pending, backlog }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Captured on x86_64: a listener on 127.0.0.1:3000 with two clients
+    const TCP: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0BB8 00000000:0000 0A 00000000:00000003 00:00000000 00000000  1000        0 41231 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:0BB8 0100007F:D431 01 00000000:00000000 00:00000000 00000000  1000        0 41240 1 0000000000000000 20 4 30 10 -1
+   2: 0100007F:0BB8 0100007F:D433 01 00000000:00000000 00:00000000 00000000  1000        0 41242 1 0000000000000000 20 4 30 10 -1
+   3: 0100007F:D431 0100007F:0BB8 01 00000000:00000000 00:00000000 00000000  1000        0 41239 1 0000000000000000 20 4 30 10 -1
+   4: 0100007F:0BB8 0100007F:D435 06 00000000:00000000 03:00000F9E 00000000     0        0 0 3 0000000000000000
+";
+
+    const TCP6: &str = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:1F90 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000   999        0 52000 1 0000000000000000 100 0 0 10 0
+   1: 00000000000000000000000001000000:1F90 00000000000000000000000001000000:E2A0 01 00000000:00000000 00:00000000 00000000   999        0 52010 1 0000000000000000 20 4 30 10 -1
+";
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn parses_v4_table() {
+        let entries = parse_table(TCP);
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[0].local, "127.0.0.1:3000".parse().unwrap());
+        assert_eq!(entries[0].state, TcpState::Listen);
+        assert_eq!(entries[0].rx_queue, 3);
+        assert_eq!(entries[0].uid, 1000);
+        assert_eq!(entries[0].inode, 41231);
+        assert_eq!(entries[1].remote, "127.0.0.1:54321".parse().unwrap());
+        assert_eq!(entries[4].state, TcpState::TimeWait);
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn parses_v6_table() {
+        let entries = parse_table(TCP6);
+        assert_eq!(entries[0].local, "[::]:8080".parse().unwrap());
+        assert_eq!(entries[1].local, "[::1]:8080".parse().unwrap());
+        assert_eq!(entries[1].state, TcpState::Established);
+    }
+
+    #[test]
+    fn counts_established_per_listener() {
+        let mut entries = parse_table(TCP);
+        entries.extend(parse_table(TCP6));
+        let counts = established_counts(&entries);
+        assert_eq!(counts.get(&"127.0.0.1:3000".parse().unwrap()), Some(&2));
+        assert_eq!(counts.get(&"[::]:8080".parse().unwrap()), Some(&1));
+        // The client side of a connection has no listener behind it
+        assert_eq!(counts.len(), 2);
+    }
+
+    #[test]
+    fn listeners_on_one_port_count_separately() {
+        let entry = |local: &str, state| SocketEntry {
+            local: local.parse().unwrap(),
+            remote: "10.0.0.9:40000".parse().unwrap(),
+            state,
+            tx_queue: 0,
+            rx_queue: 0,
+            uid: 0,
+            inode: 0,
+        };
+        let entries = vec![
+            entry("127.0.0.1:8080", TcpState::Listen),
+            entry("0.0.0.0:8080", TcpState::Listen),
+            entry("127.0.0.1:8080", TcpState::Established),
+            entry("10.0.0.5:8080", TcpState::Established),
+            entry("10.0.0.5:8080", TcpState::Established),
+            entry("10.0.0.5:51000", TcpState::Established),
+        ];
+        let counts = established_counts(&entries);
+        assert_eq!(counts.get(&"127.0.0.1:8080".parse().unwrap()), Some(&1));
+        assert_eq!(counts.get(&"0.0.0.0:8080".parse().unwrap()), Some(&2));
+        assert_eq!(counts.len(), 2);
+    }
+
+    #[test]
+    fn parses_unix_listeners() {
+        let table = "Num       RefCount Protocol Flags    Type St Inode Path
+0000000000000000: 00000002 00000000 00010000 0001 01 20923 /run/postgresql/.s.PGSQL.5432
+0000000000000000: 00000002 00000000 00010000 0001 01 18841 @/tmp/.X11-unix/X0
+0000000000000000: 00000003 00000000 00000000 0001 03 31337 /run/postgresql/.s.PGSQL.5432
+0000000000000000: 00000002 00000000 00010000 0001 01 40404
+0000000000000000: 00000002 00000000 00010000 0005 01 40500 /tmp/my socket
+";
+        let listeners = parse_unix_table(table);
+        let paths: Vec<_> = listeners.iter().map(|l| l.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["/run/postgresql/.s.PGSQL.5432", "@/tmp/.X11-unix/X0", "/tmp/my socket"]
+        );
+        assert_eq!(listeners[0].inode, 20923);
+    }
+
+    #[test]
+    fn fuller_queue_wins() {
+        let idle = AcceptQueue { pending: 0, backlog: 511 };
+        let busy = AcceptQueue { pending: 100, backlog: 128 };
+        assert_eq!(idle.fuller(busy), busy);
+        assert_eq!(busy.fuller(idle), busy);
+        assert_eq!(AcceptQueue { pending: 3, backlog: 0 }.fill(), 0.0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parses_diag_dump() {
+        fn message(kind: u16, payload: &[u8]) -> Vec<u8> {
+            let mut msg = Vec::new();
+            msg.extend(((16 + payload.len()) as u32).to_ne_bytes());
+            msg.extend(kind.to_ne_bytes());
+            msg.extend([0u8; 10]);
+            msg.extend(payload);
+            msg
+        }
+        let mut v4 = vec![0u8; 72];
+        v4[0] = libc::AF_INET as u8;
+        v4[4..6].copy_from_slice(&3000u16.to_be_bytes());
+        v4[8..12].copy_from_slice(&[127, 0, 0, 1]);
+        v4[56..60].copy_from_slice(&7u32.to_ne_bytes());
+        v4[60..64].copy_from_slice(&511u32.to_ne_bytes());
+        let mut v6 = vec![0u8; 72];
+        v6[0] = libc::AF_INET6 as u8;
+        v6[4..6].copy_from_slice(&3000u16.to_be_bytes());
+        v6[8..24].copy_from_slice(&Ipv6Addr::LOCALHOST.octets());
+        v6[60..64].copy_from_slice(&128u32.to_ne_bytes());
+
+        let mut buf = message(20, &v4);
+        buf.extend(message(20, &v6));
+        buf.extend(message(3, &[0u8; 4]));
+        let mut out = Vec::new();
+        assert!(diag::parse_messages(&buf, &mut out).unwrap());
+        assert_eq!(
+            out,
+            vec![
+                ("127.0.0.1:3000".parse().unwrap(), AcceptQueue { pending: 7, backlog: 511 }),
+                ("[::1]:3000".parse().unwrap(), AcceptQueue { pending: 0, backlog: 128 }),
+            ]
+        );
+
+        let error = message(2, &(-13i32).to_ne_bytes());
+        assert_eq!(
+            diag::parse_messages(&error, &mut out).unwrap_err().raw_os_error(),
+            Some(13)
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn sees_own_listener() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let queues = accept_queues().expect("sock_diag available");
+        assert!(queues.get(&listener.local_addr().unwrap()).is_some_and(|q| q.backlog > 0));
+    }
+}
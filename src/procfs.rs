@@ -0,0 +1,300 @@
+//! Readers for per-process details sysinfo doesn't expose. These only exist
+//! on Linux; other platforms get `None` and the UI shows a dash.
+
+use std::collections::{HashMap, HashSet};
+
+#[cfg(target_os = "linux")]
+fn read(pid: u32, file: &str) -> Option<String> {
+    std::fs::read_to_string(format!("/proc/{}/{}", pid, file)).ok()
+}
+
+#[cfg(target_os = "linux")]
+pub fn thread_count(pid: u32) -> Option<u32> {
+    status_field(&read(pid, "status")?, "Threads")?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn thread_count(_pid: u32) -> Option<u32> {
+    None
+}
+
+/// Memory figures beyond sysinfo's resident size, in bytes. `smaps_rollup`
+/// needs ptrace access, so PSS and USS are missing for other users' processes.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct MemoryBreakdown {
+    /// Proportional set size: shared pages split evenly between their sharers,
+    /// so a forked worker pool adds up to its real footprint
+    pub pss: Option<u64>,
+    /// Unique set size: private pages, freed when the process exits
+    pub uss: Option<u64>,
+    pub swap: Option<u64>,
+    pub virtual_size: Option<u64>,
+}
+
+#[cfg(target_os = "linux")]
+pub fn memory_breakdown(pid: u32) -> MemoryBreakdown {
+    let mut memory = read(pid, "smaps_rollup")
+        .map(|rollup| parse_smaps_rollup(&rollup))
+        .unwrap_or_default();
+    if let Some(status) = read(pid, "status") {
+        memory.virtual_size = kb_field(&status, "VmSize");
+        memory.swap = memory.swap.or_else(|| kb_field(&status, "VmSwap"));
+    }
+    memory
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn memory_breakdown(_pid: u32) -> MemoryBreakdown {
+    MemoryBreakdown::default()
+}
+
+pub fn parse_smaps_rollup(rollup: &str) -> MemoryBreakdown {
+    let private_clean = kb_field(rollup, "Private_Clean");
+    let private_dirty = kb_field(rollup, "Private_Dirty");
+    MemoryBreakdown {
+        pss: kb_field(rollup, "Pss"),
+        uss: private_clean.zip(private_dirty).map(|(clean, dirty)| clean + dirty),
+        swap: kb_field(rollup, "Swap"),
+        virtual_size: None,
+    }
+}
+
+/// A `Key:   123 kB` line, in bytes
+fn kb_field(text: &str, key: &str) -> Option<u64> {
+    let kb: u64 = status_field(text, key)?.strip_suffix(" kB")?.trim().parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Short command name from `/proc/<pid>/comm`
+#[cfg(target_os = "linux")]
+pub fn comm(pid: u32) -> Option<String> {
+    Some(read(pid, "comm")?.trim_end().to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn comm(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Maps socket inodes to the lowest PID holding them open. Prefork servers
+/// share one listening socket, and the lowest PID is usually the master.
+/// Walks every process's fd table, so callers only do this on request.
+#[cfg(target_os = "linux")]
+pub fn socket_owners(inodes: &HashSet<u64>) -> HashMap<u64, u32> {
+    let mut owners: HashMap<u64, u32> = HashMap::new();
+    let Ok(procs) = std::fs::read_dir("/proc") else {
+        return owners;
+    };
+    for entry in procs.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else {
+            continue;
+        };
+        // Other users' fd tables aren't readable without root
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let inode = std::fs::read_link(fd.path())
+                .ok()
+                .and_then(|target| parse_socket_link(&target.to_string_lossy()));
+            if let Some(inode) = inode.filter(|i| inodes.contains(i)) {
+                let owner = owners.entry(inode).or_insert(pid);
+                *owner = (*owner).min(pid);
+            }
+        }
+    }
+    owners
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn socket_owners(_inodes: &HashSet<u64>) -> HashMap<u64, u32> {
+    HashMap::new()
+}
+
+/// `socket_owners` kept between scans. Listening sockets rarely change, so
+/// the fd tables are only walked again when an unseen inode shows up.
+#[derive(Default)]
+pub struct SocketOwnerCache {
+    /// Inodes the last walk looked for, owned or not; other users' sockets
+    /// never get an owner and shouldn't force a walk every scan
+    seen: HashSet<u64>,
+    owners: HashMap<u64, u32>,
+}
+
+impl SocketOwnerCache {
+    pub fn owners(&mut self, inodes: HashSet<u64>) -> &HashMap<u64, u32> {
+        self.update(inodes, socket_owners)
+    }
+
+    fn update(
+        &mut self,
+        inodes: HashSet<u64>,
+        walk: impl FnOnce(&HashSet<u64>) -> HashMap<u64, u32>,
+    ) -> &HashMap<u64, u32> {
+        if inodes.is_subset(&self.seen) {
+            self.owners.retain(|inode, _| inodes.contains(inode));
+        } else {
+            self.owners = walk(&inodes);
+        }
+        self.seen = inodes;
+        &self.owners
+    }
+}
+
+/// `socket:[12345]` -> 12345
+pub fn parse_socket_link(target: &str) -> Option<u64> {
+    target.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+}
+
+/// Environment the process was started with; needs the same user (or root)
+#[cfg(target_os = "linux")]
+pub fn environ(pid: u32) -> Result<Vec<(String, String)>, String> {
+    let bytes = std::fs::read(format!("/proc/{}/environ", pid)).map_err(|e| e.to_string())?;
+    Ok(parse_environ(&bytes))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn environ(_pid: u32) -> Result<Vec<(String, String)>, String> {
+    Err("only available on Linux".to_string())
+}
+
+/// NUL-separated `KEY=value` entries
+pub fn parse_environ(bytes: &[u8]) -> Vec<(String, String)> {
+    bytes
+        .split(|b| *b == 0)
+        .filter_map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let (key, value) = entry.split_once('=')?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Open file descriptors; needs the same user as the process (or root)
+#[cfg(target_os = "linux")]
+pub fn fd_count(pid: u32) -> Option<u32> {
+    let entries = std::fs::read_dir(format!("/proc/{}/fd", pid)).ok()?;
+    Some(entries.count() as u32)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn fd_count(_pid: u32) -> Option<u32> {
+    None
+}
+
+/// Soft `RLIMIT_NOFILE`, i.e. the point where `open` starts failing with EMFILE
+#[cfg(target_os = "linux")]
+pub fn fd_soft_limit(pid: u32) -> Option<u64> {
+    parse_fd_limit(&read(pid, "limits")?)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn fd_soft_limit(_pid: u32) -> Option<u64> {
+    None
+}
+
+/// Value of a `Key:\tvalue` line from `/proc/<pid>/status`
+pub fn status_field<'a>(status: &'a str, key: &str) -> Option<&'a str> {
+    status.lines().find_map(|line| {
+        let (k, v) = line.split_once(':')?;
+        (k == key).then(|| v.trim())
+    })
+}
+
+pub fn parse_fd_limit(limits: &str) -> Option<u64> {
+    let line = limits.lines().find(|l| l.starts_with("Max open files"))?;
+    let soft = line["Max open files".len()..].split_whitespace().next()?;
+    // "unlimited" can't be exhausted, so there's nothing to warn about
+    soft.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: &str = "Limit                     Soft Limit           Hard Limit           Units     
+Max cpu time              unlimited            unlimited            seconds   
+Max open files            1024                 524288               files     
+Max locked memory         8388608              8388608              bytes     
+";
+
+    #[test]
+    fn parses_soft_fd_limit() {
+        assert_eq!(parse_fd_limit(LIMITS), Some(1024));
+        assert_eq!(
+            parse_fd_limit("Max open files            unlimited            unlimited            files"),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_socket_links() {
+        assert_eq!(parse_socket_link("socket:[20923]"), Some(20923));
+        assert_eq!(parse_socket_link("pipe:[20923]"), None);
+        assert_eq!(parse_socket_link("/dev/null"), None);
+    }
+
+    #[test]
+    fn socket_owners_walk_only_for_new_inodes() {
+        let mut cache = SocketOwnerCache::default();
+        let mut walks = 0;
+        let mut walk = |inodes: &HashSet<u64>| {
+            walks += 1;
+            inodes.iter().filter(|i| **i != 3).map(|i| (*i, 100)).collect()
+        };
+        assert_eq!(cache.update(HashSet::from([1, 2, 3]), &mut walk).len(), 2);
+        // Gone and unowned inodes don't trigger a walk; a new one does
+        assert_eq!(cache.update(HashSet::from([1, 3]), &mut walk).len(), 1);
+        assert_eq!(cache.update(HashSet::from([1, 3, 4]), &mut walk).get(&4), Some(&100));
+        assert_eq!(walks, 2);
+    }
+
+    #[test]
+    fn parses_environ_entries() {
+        let vars = parse_environ(b"PORT=3000\0DATABASE_URL=postgres://db/a=b\0junk\0");
+        assert_eq!(
+            vars,
+            vec![
+                ("PORT".to_string(), "3000".to_string()),
+                ("DATABASE_URL".to_string(), "postgres://db/a=b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_smaps_rollup() {
+        let rollup = "55dff908f000-7ffd8c4f6000 ---p 00000000 00:00 0    [rollup]
+Rss:                1408 kB
+Pss:                 317 kB
+Shared_Clean:       1260 kB
+Private_Clean:        48 kB
+Private_Dirty:       100 kB
+Swap:                 12 kB
+";
+        let memory = parse_smaps_rollup(rollup);
+        assert_eq!(memory.pss, Some(317 * 1024));
+        assert_eq!(memory.uss, Some(148 * 1024));
+        assert_eq!(memory.swap, Some(12 * 1024));
+        assert_eq!(memory.virtual_size, None);
+    }
+
+    #[test]
+    fn reads_status_fields() {
+        let status = "Name:\tnode\nState:\tS (sleeping)\nThreads:\t11\n";
+        assert_eq!(status_field(status, "Threads"), Some("11"));
+        assert_eq!(status_field(status, "State"), Some("S (sleeping)"));
+        assert_eq!(status_field(status, "VmSwap"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn reads_own_process() {
+        let pid = std::process::id();
+        assert!(thread_count(pid).unwrap() >= 1);
+        assert!(comm(pid).is_some_and(|name| !name.is_empty()));
+        assert!(fd_count(pid).unwrap() >= 3);
+        assert!(fd_soft_limit(pid).is_some());
+        let memory = memory_breakdown(pid);
+        assert!(memory.pss.is_some() && memory.virtual_size.is_some());
+    }
+}
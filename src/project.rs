@@ -0,0 +1,183 @@
+use std::fs;
+use std::path::Path;
+
+/// Files that mark the root of a project, checked in order in each directory
+const MARKERS: &[&str] = &[
+    "package.json",
+    "Cargo.toml",
+    "pyproject.toml",
+    "go.mod",
+    "Gemfile",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+    "composer.json",
+    "deno.json",
+    "mix.exs",
+    "setup.py",
+];
+
+/// Walks up from `cwd` to the nearest project marker and returns the project
+/// name, falling back to the directory name when the marker has none.
+pub fn detect(cwd: &Path) -> Option<String> {
+    for dir in cwd.ancestors() {
+        // Daemons usually run from `/`; a marker there says nothing about them
+        if dir.parent().is_none() {
+            break;
+        }
+        for marker in MARKERS {
+            let path = dir.join(marker);
+            if !path.is_file() {
+                continue;
+            }
+            let contents = fs::read_to_string(&path).unwrap_or_default();
+            let name = parse_name(marker, &contents).or_else(|| dir_name(dir));
+            if name.is_some() {
+                return name;
+            }
+        }
+    }
+
+    None
+}
+
+fn dir_name(dir: &Path) -> Option<String> {
+    dir.file_name().map(|n| n.to_string_lossy().to_string())
+}
+
+pub fn parse_name(marker: &str, contents: &str) -> Option<String> {
+    match marker {
+        "package.json" | "composer.json" | "deno.json" => json_name(contents),
+        "Cargo.toml" => toml_name(contents, &["package"]),
+        "pyproject.toml" => toml_name(contents, &["project", "tool.poetry"]),
+        "go.mod" => go_module_name(contents),
+        "pom.xml" => pom_artifact_id(contents),
+        _ => None,
+    }
+}
+
+fn json_name(contents: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(contents).ok()?;
+    let name = value.get("name")?.as_str()?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Reads `name = "..."` from the first of `sections` that defines it. This is
+/// deliberately a line scanner rather than a TOML parser; manifests keep the
+/// name as a plain string on its own line.
+fn toml_name(contents: &str, sections: &[&str]) -> Option<String> {
+    let mut current = String::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = header.trim().to_string();
+            continue;
+        }
+        if !sections.contains(&current.as_str()) {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if key.trim() == "name" {
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+            if !value.is_empty() {
+                return Some(value.to_string());
+            }
+        }
+    }
+    None
+}
+
+fn go_module_name(contents: &str) -> Option<String> {
+    let module = contents
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("module "))?
+        .trim()
+        .trim_matches('"');
+    // Major-version suffixes like `/v2` aren't part of the project's name
+    let mut segments = module.rsplit('/');
+    let last = segments.next()?;
+    let is_version = last.len() > 1
+        && last.starts_with('v')
+        && last[1..].chars().all(|c| c.is_ascii_digit());
+    let name = if is_version { segments.next()? } else { last };
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn pom_artifact_id(contents: &str) -> Option<String> {
+    // Skip the <parent> block so we don't report the parent's artifactId
+    let body = match (contents.find("<parent>"), contents.find("</parent>")) {
+        (Some(start), Some(end)) if start < end => {
+            format!("{}{}", &contents[..start], &contents[end..])
+        }
+        _ => contents.to_string(),
+    };
+    let start = body.find("<artifactId>")? + "<artifactId>".len();
+    let end = body[start..].find("</artifactId>")? + start;
+    let name = body[start..end].trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_package_json() {
+        let json = r#"{ "version": "1.0.0", "name": "billing-api", "scripts": {} }"#;
+        assert_eq!(parse_name("package.json", json), Some("billing-api".into()));
+        assert_eq!(parse_name("package.json", "{}"), None);
+    }
+
+    #[test]
+    fn parses_cargo_toml() {
+        let toml = "[workspace]\nmembers = []\n\n[package]\nname = \"srvtop\"\nversion = \"0.1.0\"\n";
+        assert_eq!(parse_name("Cargo.toml", toml), Some("srvtop".into()));
+        assert_eq!(parse_name("Cargo.toml", "[workspace]\nname = \"x\"\n"), None);
+    }
+
+    #[test]
+    fn parses_pyproject() {
+        let pep621 = "[project]\nname = 'reports'\n";
+        let poetry = "[tool.poetry]\nname = \"legacy-app\"\n";
+        assert_eq!(parse_name("pyproject.toml", pep621), Some("reports".into()));
+        assert_eq!(parse_name("pyproject.toml", poetry), Some("legacy-app".into()));
+    }
+
+    #[test]
+    fn parses_go_mod() {
+        assert_eq!(
+            parse_name("go.mod", "module github.com/acme/gateway\n\ngo 1.22\n"),
+            Some("gateway".into())
+        );
+        assert_eq!(
+            parse_name("go.mod", "module github.com/acme/gateway/v2\n"),
+            Some("gateway".into())
+        );
+    }
+
+    #[test]
+    fn parses_pom_skipping_parent() {
+        let pom = "<project><parent><artifactId>spring-boot-starter-parent</artifactId></parent>\
+                   <artifactId>inventory</artifactId></project>";
+        assert_eq!(parse_name("pom.xml", pom), Some("inventory".into()));
+    }
+
+    #[test]
+    fn detect_walks_up_to_marker() {
+        let root = std::env::temp_dir().join(format!("srvtop-project-{}", std::process::id()));
+        let nested = root.join("web").join("src");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join("package.json"), r#"{"name": "storefront"}"#).unwrap();
+
+        assert_eq!(detect(&nested), Some("storefront".into()));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn detect_ignores_root() {
+        assert_eq!(detect(Path::new("/")), None);
+    }
+}
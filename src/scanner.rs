@@ -1,15 +1,250 @@
-use sysinfo::{Pid, System};
+use std::ffi::OsString;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::time::Duration;
 
+use sysinfo::{Pid, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, System, UpdateKind, Uid, Users};
+
+use crate::container::{self, Container};
+use crate::framework::{self, Framework};
+use crate::netstat::{self, AcceptQueue};
+use crate::procfs::{self, MemoryBreakdown, SocketOwnerCache};
+use crate::tree::{ProcNode, ProcessTable};
+
+#[derive(Clone)]
 pub struct DevProcess {
     pub pid: u32,
     pub name: String,
+    pub address: IpAddr,
     pub port: u16,
     pub protocol: String,
+    pub family: AddrFamily,
+    /// Path of a Unix domain socket; such rows have port 0 and a loopback address
+    pub socket_path: Option<String>,
+    pub other_ports: Vec<PortBinding>,
     pub cpu_percent: f32,
     pub memory_bytes: u64,
     pub memory_display: String,
+    pub memory_detail: MemoryBreakdown,
     pub uptime_secs: u64,
     pub uptime_display: String,
+    pub state: ProcState,
+    pub cmdline: String,
+    pub cwd: Option<PathBuf>,
+    pub exe: Option<PathBuf>,
+    pub project: Option<String>,
+    pub framework: Option<Framework>,
+    pub ancestors: Vec<ProcNode>,
+    pub descendants: Vec<ProcNode>,
+    pub uid: Option<Uid>,
+    pub user: Option<String>,
+    pub container: Option<Container>,
+    /// ESTABLISHED connections on the port; `None` where it can't be counted
+    pub connections: Option<u32>,
+    /// Accept-queue fill for TCP listeners (Linux only)
+    pub accept_queue: Option<AcceptQueue>,
+    /// Cumulative bytes read/written by the process
+    pub io_read_total: u64,
+    pub io_write_total: u64,
+    /// Bytes per second since the previous refresh; `None` on first sight
+    pub io_read_rate: Option<u64>,
+    pub io_write_rate: Option<u64>,
+    pub threads: Option<u32>,
+    pub fds: Option<u32>,
+    pub fd_limit: Option<u64>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Exposure {
+    /// Bound to loopback, only reachable from this machine
+    Local,
+    /// Bound to a specific non-loopback interface (LAN, VPN, ...)
+    Interface,
+    /// Bound to the wildcard address, reachable on every interface
+    Everywhere,
+}
+
+/// Scheduler state, from sysinfo's process status
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ProcState {
+    Running,
+    Sleeping,
+    /// Suspended by SIGSTOP, Ctrl-Z or a debugger; keeps its ports but never answers
+    Stopped,
+    Zombie,
+    /// Uninterruptible sleep, usually waiting on disk or NFS
+    DiskWait,
+    Unknown,
+}
+
+impl ProcState {
+    pub fn from_status(status: ProcessStatus) -> Self {
+        match status {
+            ProcessStatus::Run | ProcessStatus::Waking => ProcState::Running,
+            ProcessStatus::Sleep | ProcessStatus::Idle | ProcessStatus::Parked => ProcState::Sleeping,
+            ProcessStatus::Stop | ProcessStatus::Tracing => ProcState::Stopped,
+            ProcessStatus::Zombie | ProcessStatus::Dead => ProcState::Zombie,
+            ProcessStatus::UninterruptibleDiskSleep => ProcState::DiskWait,
+            _ => ProcState::Unknown,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ProcState::Running => "running",
+            ProcState::Sleeping => "sleeping",
+            ProcState::Stopped => "stopped",
+            ProcState::Zombie => "zombie",
+            ProcState::DiskWait => "disk-wait",
+            ProcState::Unknown => "unknown",
+        }
+    }
+
+    pub fn parse(label: &str) -> Option<Self> {
+        [
+            ProcState::Running,
+            ProcState::Sleeping,
+            ProcState::Stopped,
+            ProcState::Zombie,
+            ProcState::DiskWait,
+            ProcState::Unknown,
+        ]
+        .into_iter()
+        .find(|s| s.label().eq_ignore_ascii_case(label))
+    }
+
+    pub fn glyph(self) -> &'static str {
+        match self {
+            ProcState::Running => "\u{25cf}",
+            ProcState::Sleeping => "\u{25cb}",
+            ProcState::Stopped => "\u{23f8}",
+            ProcState::Zombie => "\u{2620}",
+            ProcState::DiskWait => "\u{25d2}",
+            ProcState::Unknown => "?",
+        }
+    }
+}
+
+/// One listener of a grouped process, beyond the row's primary port
+#[derive(Clone)]
+pub struct PortBinding {
+    pub address: IpAddr,
+    pub port: u16,
+    pub protocol: String,
+    pub family: AddrFamily,
+    pub socket_path: Option<String>,
+}
+
+impl PortBinding {
+    /// `:3000`, or the file name of a Unix socket
+    pub fn label(&self) -> String {
+        match self.socket_path {
+            Some(ref path) => socket_name(path).to_string(),
+            None => format!(":{}", self.port),
+        }
+    }
+}
+
+/// Last path component of a socket path, e.g. `.s.PGSQL.5432`
+pub fn socket_name(path: &str) -> &str {
+    path.trim_end_matches('/').rsplit('/').next().unwrap_or(path)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AddrFamily {
+    V4,
+    V6,
+    Both,
+}
+
+impl AddrFamily {
+    pub fn of(address: IpAddr) -> Self {
+        match address {
+            IpAddr::V4(_) => AddrFamily::V4,
+            IpAddr::V6(_) => AddrFamily::V6,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AddrFamily::V4 => "v4",
+            AddrFamily::V6 => "v6",
+            AddrFamily::Both => "v4+6",
+        }
+    }
+
+    fn merge(self, other: AddrFamily) -> Self {
+        if self == other {
+            self
+        } else {
+            AddrFamily::Both
+        }
+    }
+}
+
+pub fn exposure(address: IpAddr) -> Exposure {
+    let address = address.to_canonical();
+    if address.is_loopback() {
+        Exposure::Local
+    } else if address.is_unspecified() {
+        Exposure::Everywhere
+    } else {
+        Exposure::Interface
+    }
+}
+
+pub fn format_address(address: IpAddr) -> String {
+    match address {
+        IpAddr::V4(v4) => v4.to_string(),
+        IpAddr::V6(v6) => format!("[{}]", v6),
+    }
+}
+
+impl DevProcess {
+    /// Open fds as a share of the soft limit
+    pub fn fd_usage(&self) -> Option<f64> {
+        match (self.fds, self.fd_limit) {
+            (Some(fds), Some(limit)) if limit > 0 => Some(fds as f64 / limit as f64),
+            _ => None,
+        }
+    }
+
+    /// Exposure of the most reachable listener on this row
+    pub fn exposure(&self) -> Exposure {
+        self.bindings()
+            .map(|b| exposure(b.address))
+            .max_by_key(|e| exposure_rank(*e))
+            .unwrap_or(Exposure::Local)
+    }
+
+    /// The primary listener followed by any grouped ones
+    pub fn bindings(&self) -> impl Iterator<Item = PortBinding> + '_ {
+        let primary = PortBinding {
+            address: self.address,
+            port: self.port,
+            protocol: self.protocol.clone(),
+            family: self.family,
+            socket_path: self.socket_path.clone(),
+        };
+        std::iter::once(primary).chain(self.other_ports.iter().cloned())
+    }
+
+    pub fn has_port(&self, port: u16) -> bool {
+        self.bindings().any(|b| b.socket_path.is_none() && b.port == port)
+    }
+
+    pub fn is_unix(&self) -> bool {
+        self.socket_path.is_some()
+    }
+
+    pub fn port_label(&self) -> String {
+        let primary = self.bindings().next().map(|b| b.label()).unwrap_or_default();
+        if self.other_ports.is_empty() {
+            primary
+        } else {
+            format!("{} +{}", primary, self.other_ports.len())
+        }
+    }
 }
 
 pub fn format_uptime(secs: u64) -> String {
@@ -40,6 +275,19 @@ pub fn format_uptime(secs: u64) -> String {
     }
 }
 
+pub fn format_rate(bytes_per_sec: u64) -> String {
+    format!("{}/s", format_bytes(bytes_per_sec))
+}
+
+/// Average throughput between two cumulative byte counters
+pub fn rate_per_sec(previous: u64, current: u64, elapsed: Duration) -> u64 {
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 {
+        return 0;
+    }
+    (current.saturating_sub(previous) as f64 / secs).round() as u64
+}
+
 pub fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
@@ -56,63 +304,390 @@ pub fn format_bytes(bytes: u64) -> String {
     }
 }
 
-pub fn scan(system: &System) -> Result<Vec<DevProcess>, String> {
+/// Whole-machine CPU load as of the last refresh
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MachineCpu {
+    /// Share of all logical cores in use, 0-100
+    pub usage: f32,
+    pub cores: usize,
+}
+
+/// Reads machine CPU load after `scan` refreshed `system`. On Linux the
+/// per-process CPU refresh already updates the global counters, and refreshing
+/// them again here would skew the per-process deltas.
+pub fn machine_cpu(system: &mut System) -> MachineCpu {
+    #[cfg(not(target_os = "linux"))]
+    system.refresh_cpu_usage();
+    MachineCpu {
+        usage: system.global_cpu_usage(),
+        cores: system.cpus().len().max(1),
+    }
+}
+
+/// UID srvtop itself runs as, used to tell our servers from other users'
+pub fn current_uid(system: &mut System) -> Option<Uid> {
+    let pid = sysinfo::get_current_pid().ok()?;
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        false,
+        ProcessRefreshKind::nothing().with_user(UpdateKind::OnlyIfNotSet),
+    );
+    system.process(pid)?.user_id().cloned()
+}
+
+/// `pid`'s current name, to tell whether it was reused since it was read
+pub fn process_name(system: &mut System, pid: u32) -> Option<String> {
+    let pid = Pid::from_u32(pid);
+    system.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, ProcessRefreshKind::nothing());
+    Some(system.process(pid)?.name().to_string_lossy().to_string())
+}
+
+/// Whether `uid` may signal any process (root on Unix)
+pub fn is_privileged(uid: &Uid) -> bool {
+    #[cfg(unix)]
+    {
+        **uid == 0
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = uid;
+        false
+    }
+}
+
+/// Joins argv into a single shell-like line, quoting arguments with spaces
+pub fn format_command(cmd: &[OsString]) -> String {
+    cmd.iter()
+        .map(|arg| {
+            let arg = arg.to_string_lossy();
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                format!("\"{}\"", arg)
+            } else {
+                arg.into_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// What `scan` refreshes for each listening PID; everything else on the
+/// machine is left alone
+fn listener_refresh_kind() -> ProcessRefreshKind {
+    ProcessRefreshKind::nothing()
+        .with_cpu()
+        .with_memory()
+        .with_disk_usage()
+        .with_user(UpdateKind::OnlyIfNotSet)
+        .with_cmd(UpdateKind::OnlyIfNotSet)
+        .with_cwd(UpdateKind::OnlyIfNotSet)
+        .with_exe(UpdateKind::OnlyIfNotSet)
+}
+
+/// A listening socket before its process details are filled in
+struct Socket {
+    pid: u32,
+    /// Name and exe as seen by the socket scan, for when sysinfo can't read
+    /// the process
+    name: String,
+    exe: String,
+    address: IpAddr,
+    port: u16,
+    protocol: String,
+    path: Option<String>,
+}
+
+/// Named Unix sockets in the listening state with the PID that owns each
+fn unix_sockets(owner_cache: &mut SocketOwnerCache) -> Vec<Socket> {
+    let Some(listeners) = netstat::unix_listeners() else {
+        return Vec::new();
+    };
+    let inodes = listeners.iter().map(|l| l.inode).collect();
+    let owners = owner_cache.owners(inodes);
+    listeners
+        .into_iter()
+        .filter_map(|l| {
+            Some(Socket {
+                pid: *owners.get(&l.inode)?,
+                name: String::new(),
+                exe: String::new(),
+                address: IpAddr::from([127, 0, 0, 1]),
+                port: 0,
+                protocol: "UNIX".to_string(),
+                path: Some(l.path),
+            })
+        })
+        .collect()
+}
+
+/// Lists sockets first, then refreshes only their owning PIDs in `system`.
+/// Ancestors and descendants are filled in when a process `table` is given;
+/// Unix socket listeners are included when `unix` is given, and the memory
+/// breakdown is read when `memory_detail` is set.
+pub fn scan(
+    system: &mut System,
+    users: &Users,
+    table: Option<&ProcessTable>,
+    unix: Option<&mut SocketOwnerCache>,
+    memory_detail: bool,
+) -> Result<Vec<DevProcess>, String> {
     let listeners = match listeners::get_all() {
         Ok(l) => l,
         Err(e) => return Err(format!("Failed to scan ports: {}", e)),
     };
 
+    let mut sockets: Vec<Socket> = listeners
+        .into_iter()
+        // Unbound UDP sockets (port 0) are client-side, not services
+        .filter(|l| !(l.protocol == listeners::Protocol::UDP && l.socket.port() == 0))
+        .map(|l| Socket {
+            pid: l.process.pid,
+            name: l.process.name,
+            exe: l.process.path,
+            address: l.socket.ip(),
+            port: l.socket.port(),
+            protocol: format!("{:?}", l.protocol),
+            path: None,
+        })
+        .collect();
+    if let Some(owner_cache) = unix {
+        sockets.extend(unix_sockets(owner_cache));
+    }
+
+    let mut pids: Vec<Pid> = sockets
+        .iter()
+        .map(|s| Pid::from_u32(s.pid))
+        .collect();
+    pids.sort_unstable();
+    pids.dedup();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&pids),
+        true,
+        listener_refresh_kind(),
+    );
+
+    let connection_counts = netstat::tcp_sockets().map(|s| netstat::established_counts(&s));
+    let accept_queues = netstat::accept_queues();
     let mut processes = Vec::new();
 
-    for listener in listeners {
-        let pid = listener.process.pid;
-        let port = listener.socket.port();
-        let protocol = format!("{:?}", listener.protocol);
+    for socket in sockets {
+        let Socket { pid, address, port, protocol, .. } = socket;
+
+        let sys_proc = system.process(Pid::from(pid as usize));
 
-        let (name, cpu_percent, memory_bytes, uptime_secs) =
-            if let Some(proc) = system.process(Pid::from(pid as usize)) {
+        let (name, cpu_percent, memory_bytes, uptime_secs, state) =
+            if let Some(proc) = sys_proc {
                 (
                     proc.name().to_string_lossy().to_string(),
                     proc.cpu_usage(),
                     proc.memory(),
                     proc.run_time(),
+                    ProcState::from_status(proc.status()),
                 )
             } else {
-                (listener.process.name.clone(), 0.0, 0, 0)
+                (socket.name, 0.0, 0, 0, ProcState::Unknown)
             };
 
+        let (cmdline, cwd, exe) = if let Some(proc) = sys_proc {
+            (
+                format_command(proc.cmd()),
+                proc.cwd().map(|p| p.to_path_buf()),
+                proc.exe().map(|p| p.to_path_buf()),
+            )
+        } else {
+            let exe = socket.exe;
+            (String::new(), None, (!exe.is_empty()).then(|| PathBuf::from(exe)))
+        };
+
+        let connections = match connection_counts {
+            Some(ref counts) if protocol == "TCP" => {
+                Some(counts.get(&SocketAddr::new(address, port)).copied().unwrap_or(0))
+            }
+            _ => None,
+        };
+
+        let accept_queue = match accept_queues {
+            Some(ref queues) if protocol == "TCP" => {
+                queues.get(&SocketAddr::new(address, port)).copied()
+            }
+            _ => None,
+        };
+
+        let (io_read_total, io_write_total) = sys_proc
+            .map(|p| p.disk_usage())
+            .map(|d| (d.total_read_bytes, d.total_written_bytes))
+            .unwrap_or((0, 0));
+
+        let uid = sys_proc.and_then(|p| p.user_id()).cloned();
+        let user = uid.as_ref().map(|uid| match users.get_user_by_id(uid) {
+            Some(u) => u.name().to_string(),
+            None => uid.to_string(),
+        });
+
+        let framework = framework::from_cmdline(&cmdline);
+        let container = container::detect(pid, &name, &cmdline);
+
         let memory_display = format_bytes(memory_bytes);
         let uptime_display = format_uptime(uptime_secs);
 
         processes.push(DevProcess {
             pid,
             name,
+            address,
             port,
             protocol,
+            family: AddrFamily::of(address),
+            socket_path: socket.path,
+            other_ports: Vec::new(),
             cpu_percent,
             memory_bytes,
             memory_display,
+            memory_detail: if memory_detail {
+                procfs::memory_breakdown(pid)
+            } else {
+                MemoryBreakdown::default()
+            },
             uptime_secs,
             uptime_display,
+            state,
+            cmdline,
+            cwd,
+            exe,
+            // Filled in by the caller, which caches it per (pid, cwd)
+            project: None,
+            framework,
+            ancestors: table.map(|t| t.ancestors(pid)).unwrap_or_default(),
+            descendants: table.map(|t| t.descendants(pid)).unwrap_or_default(),
+            uid,
+            user,
+            container,
+            connections,
+            accept_queue,
+            io_read_total,
+            io_write_total,
+            io_read_rate: None,
+            io_write_rate: None,
+            threads: procfs::thread_count(pid),
+            fds: procfs::fd_count(pid),
+            fd_limit: procfs::fd_soft_limit(pid),
         });
     }
 
-    Ok(processes)
+    Ok(merge_dual_stack(processes))
+}
+
+/// Collapses listeners that only differ by address (e.g. `0.0.0.0:3000` and
+/// `[::]:3000`) into one row per (pid, port, protocol). The merged row keeps
+/// the most exposed address so the ADDR column never understates reachability.
+pub fn merge_dual_stack(processes: Vec<DevProcess>) -> Vec<DevProcess> {
+    let mut merged: Vec<DevProcess> = Vec::with_capacity(processes.len());
+
+    for process in processes {
+        let existing = merged.iter_mut().find(|p| {
+            p.pid == process.pid
+                && p.port == process.port
+                && p.protocol == process.protocol
+                && p.socket_path == process.socket_path
+        });
+        match existing {
+            Some(row) => {
+                row.family = row.family.merge(process.family);
+                if exposure_rank(process.exposure()) > exposure_rank(row.exposure()) {
+                    row.address = process.address;
+                }
+            }
+            None => merged.push(process),
+        }
+    }
+
+    merged
+}
+
+/// Folds every listener of a PID into a single row. The lowest port becomes
+/// the row's primary port (Unix sockets come after ports); the rest are kept
+/// in `other_ports`.
+pub fn group_by_pid(mut processes: Vec<DevProcess>) -> Vec<DevProcess> {
+    processes.sort_by(|a, b| {
+        a.pid
+            .cmp(&b.pid)
+            .then(a.is_unix().cmp(&b.is_unix()))
+            .then(a.port.cmp(&b.port))
+    });
+
+    let mut grouped: Vec<DevProcess> = Vec::with_capacity(processes.len());
+    for process in processes {
+        match grouped.last_mut() {
+            Some(group) if group.pid == process.pid => {
+                group.connections = match (group.connections, process.connections) {
+                    (Some(a), Some(b)) => Some(a + b),
+                    (a, b) => a.or(b),
+                };
+                group.accept_queue = match (group.accept_queue, process.accept_queue) {
+                    (Some(a), Some(b)) => Some(a.fuller(b)),
+                    (a, b) => a.or(b),
+                };
+                group.other_ports.extend(process.bindings());
+            }
+            _ => grouped.push(process),
+        }
+    }
+
+    grouped
+}
+
+fn exposure_rank(exposure: Exposure) -> u8 {
+    match exposure {
+        Exposure::Local => 0,
+        Exposure::Interface => 1,
+        Exposure::Everywhere => 2,
+    }
 }
 
-#[cfg(test)]
 impl DevProcess {
-    pub fn test(name: &str, port: u16) -> Self {
+    /// A row with only the socket and name known, e.g. from a fixture
+    pub fn new(pid: u32, name: &str, address: IpAddr, port: u16, protocol: &str) -> Self {
         Self {
-            pid: 1000,
+            pid,
             name: name.to_string(),
+            address,
             port,
-            protocol: "TCP".to_string(),
+            protocol: protocol.to_string(),
+            family: AddrFamily::of(address),
+            socket_path: None,
+            other_ports: Vec::new(),
             cpu_percent: 0.0,
             memory_bytes: 0,
-            memory_display: "0 B".to_string(),
+            memory_display: format_bytes(0),
+            memory_detail: MemoryBreakdown::default(),
             uptime_secs: 0,
-            uptime_display: "0s".to_string(),
+            uptime_display: format_uptime(0),
+            state: ProcState::Unknown,
+            cmdline: String::new(),
+            cwd: None,
+            exe: None,
+            project: None,
+            framework: None,
+            ancestors: Vec::new(),
+            descendants: Vec::new(),
+            uid: None,
+            user: None,
+            container: None,
+            connections: None,
+            accept_queue: None,
+            io_read_total: 0,
+            io_write_total: 0,
+            io_read_rate: None,
+            io_write_rate: None,
+            threads: None,
+            fds: None,
+            fd_limit: None,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn test(name: &str, port: u16) -> Self {
+        Self {
+            cmdline: name.to_string(),
+            ..Self::new(1000, name, IpAddr::from([127, 0, 0, 1]), port, "TCP")
         }
     }
 }
@@ -145,6 +720,142 @@ mod tests {
         assert_eq!(format_uptime(97200), "1d3h");
     }
 
+    #[test]
+    fn exposure_loopback() {
+        assert_eq!(exposure("127.0.0.1".parse().unwrap()), Exposure::Local);
+        assert_eq!(exposure("::1".parse().unwrap()), Exposure::Local);
+        assert_eq!(exposure("::ffff:127.0.0.1".parse().unwrap()), Exposure::Local);
+    }
+
+    #[test]
+    fn exposure_wildcard() {
+        assert_eq!(exposure("0.0.0.0".parse().unwrap()), Exposure::Everywhere);
+        assert_eq!(exposure("::".parse().unwrap()), Exposure::Everywhere);
+    }
+
+    #[test]
+    fn exposure_interface() {
+        assert_eq!(exposure("192.168.1.20".parse().unwrap()), Exposure::Interface);
+        assert_eq!(exposure("fe80::1".parse().unwrap()), Exposure::Interface);
+    }
+
+    #[test]
+    fn format_address_brackets_v6() {
+        assert_eq!(format_address("127.0.0.1".parse().unwrap()), "127.0.0.1");
+        assert_eq!(format_address("::".parse().unwrap()), "[::]");
+    }
+
+    fn listener(pid: u32, address: &str, port: u16) -> DevProcess {
+        let address: IpAddr = address.parse().unwrap();
+        DevProcess {
+            pid,
+            address,
+            family: AddrFamily::of(address),
+            ..DevProcess::test("node", port)
+        }
+    }
+
+    #[test]
+    fn merge_dual_stack_collapses_v4_and_v6() {
+        let merged = merge_dual_stack(vec![
+            listener(42, "127.0.0.1", 3000),
+            listener(42, "::", 3000),
+        ]);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].family, AddrFamily::Both);
+        assert_eq!(merged[0].exposure(), Exposure::Everywhere);
+    }
+
+    #[test]
+    fn merge_dual_stack_keeps_distinct_rows() {
+        let merged = merge_dual_stack(vec![
+            listener(42, "0.0.0.0", 3000),
+            listener(42, "0.0.0.0", 3001),
+            listener(43, "0.0.0.0", 3000),
+        ]);
+        assert_eq!(merged.len(), 3);
+        assert!(merged.iter().all(|p| p.family == AddrFamily::V4));
+    }
+
+    #[test]
+    fn unix_sockets_label_by_file_name() {
+        let mut pg = listener(812, "127.0.0.1", 0);
+        pg.protocol = "UNIX".to_string();
+        pg.socket_path = Some("/run/postgresql/.s.PGSQL.5432".to_string());
+        let mut lock = pg.clone();
+        lock.socket_path = Some("/run/postgresql/other.sock".to_string());
+
+        let merged = merge_dual_stack(vec![pg, lock, listener(812, "127.0.0.1", 5432)]);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].port_label(), ".s.PGSQL.5432");
+        assert!(!merged[0].has_port(0));
+        assert_eq!(group_by_pid(merged)[0].port_label(), ":5432 +2");
+    }
+
+    #[test]
+    fn group_by_pid_folds_ports() {
+        let mut inspector = listener(42, "127.0.0.1", 9229);
+        inspector.accept_queue = Some(AcceptQueue { pending: 0, backlog: 511 });
+        let mut http = listener(42, "0.0.0.0", 3000);
+        http.accept_queue = Some(AcceptQueue { pending: 120, backlog: 128 });
+        let grouped = group_by_pid(vec![inspector, listener(7, "127.0.0.1", 5432), http]);
+        assert_eq!(grouped.len(), 2);
+        let node = grouped.iter().find(|p| p.pid == 42).unwrap();
+        assert_eq!(node.port, 3000);
+        assert_eq!(node.port_label(), ":3000 +1");
+        assert!(node.has_port(9229));
+        assert_eq!(node.exposure(), Exposure::Everywhere);
+        assert_eq!(node.accept_queue.map(|q| q.pending), Some(120));
+    }
+
+    #[test]
+    fn format_command_joins_args() {
+        let cmd: Vec<OsString> = ["node", "server.js", "--port", "3000"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        assert_eq!(format_command(&cmd), "node server.js --port 3000");
+    }
+
+    #[test]
+    fn format_command_quotes_spaces() {
+        let cmd: Vec<OsString> = ["python3", "/tmp/my app/main.py", ""]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        assert_eq!(format_command(&cmd), "python3 \"/tmp/my app/main.py\" \"\"");
+    }
+
+    #[test]
+    fn rate_per_sec_averages_over_elapsed() {
+        assert_eq!(rate_per_sec(1000, 4000, Duration::from_secs(3)), 1000);
+        assert_eq!(rate_per_sec(0, 1024, Duration::from_millis(500)), 2048);
+    }
+
+    #[test]
+    fn rate_per_sec_handles_resets() {
+        // A recycled PID can report a smaller counter than we remembered
+        assert_eq!(rate_per_sec(5000, 100, Duration::from_secs(1)), 0);
+        assert_eq!(rate_per_sec(0, 100, Duration::ZERO), 0);
+    }
+
+    #[test]
+    fn format_rate_appends_per_second() {
+        assert_eq!(format_rate(0), "0 B/s");
+        assert_eq!(format_rate(1536), "1.5 KB/s");
+    }
+
+    #[test]
+    fn fd_usage_needs_both_counts() {
+        let mut p = DevProcess::test("node", 3000);
+        assert_eq!(p.fd_usage(), None);
+        p.fds = Some(900);
+        p.fd_limit = Some(1024);
+        assert!((p.fd_usage().unwrap() - 0.8789).abs() < 0.001);
+        p.fd_limit = Some(0);
+        assert_eq!(p.fd_usage(), None);
+    }
+
     #[test]
     fn format_bytes_zero() {
         assert_eq!(format_bytes(0), "0 B");
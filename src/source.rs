@@ -0,0 +1,565 @@
+//! Where process rows come from. The live source reads the real machine; the
+//! fake source serves fixed rows so tests and demos can drive the whole app.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde_json::Value;
+use sysinfo::{System, Uid, Users};
+
+use crate::container::Runtime;
+use crate::docker;
+use crate::filter::{self, DevFilter};
+use crate::framework::{self, Framework};
+use crate::inspect::{self, PortSocket};
+use crate::netstat::{AcceptQueue, TcpState};
+use crate::procfs::{self, MemoryBreakdown, SocketOwnerCache};
+use crate::project;
+use crate::scanner::{self, DevProcess, MachineCpu, ProcState};
+use crate::tree::{ProcNode, ProcessTable};
+use crate::worker::ScanRequest;
+
+pub trait ProcessSource: Send + Sync {
+    /// Every listener, before any filtering
+    fn scan(&self, request: ScanRequest) -> Result<Vec<DevProcess>, String>;
+
+    /// Processes to signal when killing `pid`'s tree, deepest first and the
+    /// root last, see `ProcessTable::kill_order`
+    fn kill_order(&self, pid: u32) -> Vec<ProcNode>;
+
+    fn signal(&self, pid: u32, signal: Signal) -> Result<(), String>;
+
+    /// Name `pid` runs under now, or `None` once it exited
+    fn process_name(&self, pid: u32) -> Option<String>;
+
+    /// Every TCP and UDP socket bound to or connected to `port`, in any state
+    fn port_sockets(&self, port: u16) -> Result<Vec<PortSocket>, String>;
+
+    /// `KEY=value` pairs the process was started with
+    fn environ(&self, pid: u32) -> Result<Vec<(String, String)>, String>;
+
+    /// UID srvtop acts as, for the mine filter and ownership warnings
+    fn current_uid(&self) -> Option<Uid>;
+
+    /// What DEV mode shows, built-in lists adjusted by config files
+    fn dev_filter(&self) -> &DevFilter;
+
+    /// Processes the source keeps state for, shown in debug mode
+    fn tracked(&self) -> usize {
+        0
+    }
+
+    /// Machine CPU load as of the last scan
+    fn machine_cpu(&self) -> Option<MachineCpu> {
+        None
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Signal {
+    /// Ask the process to exit
+    Term,
+    /// Suspend it, like Ctrl-Z
+    Stop,
+    /// Resume a suspended process
+    Cont,
+}
+
+impl Signal {
+    fn name(self) -> &'static str {
+        match self {
+            Signal::Term => "TERM",
+            Signal::Stop => "STOP",
+            Signal::Cont => "CONT",
+        }
+    }
+}
+
+/// Reads sockets and processes from the running system
+pub struct LiveSource {
+    state: Mutex<LiveState>,
+    dev_filter: DevFilter,
+}
+
+struct LiveState {
+    /// Holds only listening PIDs; refreshed per scan
+    system: System,
+    /// Parent/name table for the whole machine, refreshed in tree view only
+    table_system: System,
+    users: Users,
+    probe_cache: HashMap<(u32, u16), Option<Framework>>,
+    project_cache: HashMap<(u32, PathBuf), Option<String>>,
+    socket_owners: SocketOwnerCache,
+    /// `None` until two scans have given a usage delta
+    machine_cpu: Option<MachineCpu>,
+}
+
+impl LiveSource {
+    pub fn new(dev_filter: DevFilter) -> Self {
+        Self {
+            state: Mutex::new(LiveState {
+                system: System::new(),
+                table_system: System::new(),
+                users: Users::new_with_refreshed_list(),
+                probe_cache: HashMap::new(),
+                project_cache: HashMap::new(),
+                socket_owners: SocketOwnerCache::default(),
+                machine_cpu: None,
+            }),
+            dev_filter,
+        }
+    }
+}
+
+impl ProcessSource for LiveSource {
+    fn scan(&self, request: ScanRequest) -> Result<Vec<DevProcess>, String> {
+        let mut guard = self.state.lock().map_err(|e| e.to_string())?;
+        let state = &mut *guard;
+        let table = request
+            .tree
+            .then(|| ProcessTable::snapshot(&mut state.table_system));
+
+        let primed = !state.system.cpus().is_empty();
+        let mut processes = scanner::scan(
+            &mut state.system,
+            &state.users,
+            table.as_ref(),
+            request.unix.then_some(&mut state.socket_owners),
+            request.memory_detail,
+        )?;
+        state.machine_cpu = primed.then(|| scanner::machine_cpu(&mut state.system));
+        state.detect_projects(&mut processes);
+        annotate_docker(&mut processes);
+        if request.probe_http {
+            state.probe_frameworks(&mut processes, (!request.show_all).then_some(&self.dev_filter));
+        }
+        Ok(processes)
+    }
+
+    fn kill_order(&self, pid: u32) -> Vec<ProcNode> {
+        // Scans only read the process table in tree view, so read it now
+        ProcessTable::snapshot(&mut System::new()).kill_order(pid, |name| self.dev_filter.matches_name(name))
+    }
+
+    fn signal(&self, pid: u32, signal: Signal) -> Result<(), String> {
+        send_signal(pid, signal)
+    }
+
+    fn process_name(&self, pid: u32) -> Option<String> {
+        scanner::process_name(&mut System::new(), pid)
+    }
+
+    fn port_sockets(&self, port: u16) -> Result<Vec<PortSocket>, String> {
+        inspect::sockets_on(port)
+    }
+
+    fn environ(&self, pid: u32) -> Result<Vec<(String, String)>, String> {
+        procfs::environ(pid)
+    }
+
+    fn current_uid(&self) -> Option<Uid> {
+        scanner::current_uid(&mut System::new())
+    }
+
+    fn dev_filter(&self) -> &DevFilter {
+        &self.dev_filter
+    }
+
+    fn tracked(&self) -> usize {
+        self.state.lock().map_or(0, |s| s.system.processes().len())
+    }
+
+    fn machine_cpu(&self) -> Option<MachineCpu> {
+        self.state.lock().ok().and_then(|s| s.machine_cpu)
+    }
+}
+
+impl LiveState {
+    /// Names each row's project from its working directory. Walking up for
+    /// markers touches the filesystem, so results are cached per (pid, cwd).
+    fn detect_projects(&mut self, processes: &mut [DevProcess]) {
+        self.project_cache.retain(|(pid, cwd), _| {
+            processes.iter().any(|p| p.pid == *pid && p.cwd.as_ref() == Some(cwd))
+        });
+
+        for p in processes.iter_mut() {
+            let Some(cwd) = &p.cwd else { continue };
+            p.project = self
+                .project_cache
+                .entry((p.pid, cwd.clone()))
+                .or_insert_with(|| project::detect(cwd))
+                .clone();
+        }
+    }
+
+    /// Fills in frameworks the command line didn't reveal by probing each TCP
+    /// listener once. Results are cached per (pid, port) so a server is only
+    /// probed again after it restarts. With `dev_filter`, only rows it lets
+    /// through are probed.
+    fn probe_frameworks(&mut self, processes: &mut [DevProcess], dev_filter: Option<&DevFilter>) {
+        self.probe_cache
+            .retain(|(pid, port), _| processes.iter().any(|p| p.pid == *pid && p.port == *port));
+
+        for p in processes.iter_mut() {
+            if p.framework.is_some() || p.protocol != "TCP" {
+                continue;
+            }
+            // Hidden non-dev ports (sshd, cups, ...) shouldn't get HTTP requests
+            if dev_filter.is_some_and(|f| !f.is_dev_relevant(p)) {
+                continue;
+            }
+            // Nor should databases, which are dev-relevant but not HTTP
+            if !filter::may_speak_http(p) {
+                continue;
+            }
+            p.framework = *self
+                .probe_cache
+                .entry((p.pid, p.port))
+                .or_insert_with(|| framework::probe(p.address, p.port));
+        }
+    }
+}
+
+fn annotate_docker(processes: &mut [DevProcess]) {
+    let uses_docker = processes.iter().any(|p| {
+        p.container
+            .as_ref()
+            .is_some_and(|c| matches!(c.runtime, Runtime::Docker | Runtime::Containerd))
+    });
+    if uses_docker {
+        // Docker may not be running or readable; rows then keep their process name
+        if let Ok(containers) = docker::Client::from_env().containers() {
+            docker::annotate(processes, &containers);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: Signal) -> Result<(), String> {
+    use std::process::Command;
+    let output = Command::new("kill")
+        .args([&format!("-{}", signal.name()), &pid.to_string()])
+        .output()
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(stderr.trim().to_string())
+    }
+}
+
+#[cfg(windows)]
+fn send_signal(pid: u32, signal: Signal) -> Result<(), String> {
+    use std::process::Command;
+    if signal != Signal::Term {
+        return Err(format!("SIG{} isn't available on Windows", signal.name()));
+    }
+    let output = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .output()
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(stderr.trim().to_string())
+    }
+}
+
+/// Serves a fixed set of rows. Killing a PID removes its rows, so the next
+/// scan reflects it just like a real process exiting; stopping and
+/// continuing flip its state.
+pub struct FakeSource {
+    processes: Mutex<Vec<DevProcess>>,
+    killed: Mutex<Vec<u32>>,
+    env: HashMap<u32, Vec<(String, String)>>,
+    dev_filter: DevFilter,
+}
+
+impl FakeSource {
+    pub fn new(processes: Vec<DevProcess>) -> Self {
+        Self {
+            processes: Mutex::new(processes),
+            killed: Mutex::new(Vec::new()),
+            env: HashMap::new(),
+            dev_filter: DevFilter::default(),
+        }
+    }
+
+    pub fn with_dev_filter(mut self, dev_filter: DevFilter) -> Self {
+        self.dev_filter = dev_filter;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_env(mut self, pid: u32, vars: Vec<(String, String)>) -> Self {
+        self.env.insert(pid, vars);
+        self
+    }
+
+    /// Loads rows from a JSON fixture file, see `parse_fixture`
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Can't read {}: {}", path.display(), e))?;
+        let mut source = Self::new(parse_fixture(&contents)?);
+        source.env = fixture_env(&contents);
+        Ok(source)
+    }
+
+    /// Swaps the rows, as if processes exited and others started
+    #[cfg(test)]
+    pub fn set_processes(&self, processes: Vec<DevProcess>) {
+        if let Ok(mut current) = self.processes.lock() {
+            *current = processes;
+        }
+    }
+
+    /// PIDs killed so far, in order
+    #[cfg(test)]
+    pub fn killed(&self) -> Vec<u32> {
+        self.killed.lock().map(|k| k.clone()).unwrap_or_default()
+    }
+}
+
+impl ProcessSource for FakeSource {
+    fn scan(&self, _request: ScanRequest) -> Result<Vec<DevProcess>, String> {
+        self.processes.lock().map(|p| p.clone()).map_err(|e| e.to_string())
+    }
+
+    fn kill_order(&self, pid: u32) -> Vec<ProcNode> {
+        let processes = self.processes.lock().map(|p| p.clone()).unwrap_or_default();
+        let name = processes.iter().find(|p| p.pid == pid).map(|p| p.name.clone());
+        vec![ProcNode {
+            pid,
+            name: name.unwrap_or_default(),
+            depth: 0,
+        }]
+    }
+
+    fn signal(&self, pid: u32, signal: Signal) -> Result<(), String> {
+        let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
+        if !processes.iter().any(|p| p.pid == pid) {
+            return Err("No such process".to_string());
+        }
+        match signal {
+            Signal::Term => {
+                processes.retain(|p| p.pid != pid);
+                if let Ok(mut killed) = self.killed.lock() {
+                    killed.push(pid);
+                }
+            }
+            Signal::Stop | Signal::Cont => {
+                let state = if signal == Signal::Stop { ProcState::Stopped } else { ProcState::Sleeping };
+                for p in processes.iter_mut().filter(|p| p.pid == pid) {
+                    p.state = state;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn process_name(&self, pid: u32) -> Option<String> {
+        let processes = self.processes.lock().ok()?;
+        processes.iter().find(|p| p.pid == pid).map(|p| p.name.clone())
+    }
+
+    /// Fixture rows only know their listeners
+    fn port_sockets(&self, port: u16) -> Result<Vec<PortSocket>, String> {
+        let processes = self.processes.lock().map_err(|e| e.to_string())?;
+        Ok(processes
+            .iter()
+            .filter(|p| matches!(p.protocol.as_str(), "TCP" | "UDP") && p.port == port)
+            .map(|p| PortSocket {
+                protocol: if p.protocol == "UDP" { "UDP" } else { "TCP" },
+                state: if p.protocol == "UDP" { TcpState::Close } else { TcpState::Listen },
+                local: SocketAddr::new(p.address, p.port),
+                remote: SocketAddr::new(IpAddr::from([0, 0, 0, 0]), 0),
+                owner: Some((p.pid, p.name.clone())),
+            })
+            .collect())
+    }
+
+    fn environ(&self, pid: u32) -> Result<Vec<(String, String)>, String> {
+        let running = self.processes.lock().map_err(|e| e.to_string())?.iter().any(|p| p.pid == pid);
+        if !running {
+            return Err("No such process".to_string());
+        }
+        Ok(self.env.get(&pid).cloned().unwrap_or_default())
+    }
+
+    fn current_uid(&self) -> Option<Uid> {
+        None
+    }
+
+    fn dev_filter(&self) -> &DevFilter {
+        &self.dev_filter
+    }
+}
+
+/// Parses a fixture: a JSON array of rows. Only `pid`, `name` and `port` (or
+/// `socket` for a Unix socket path) are required, e.g.
+/// `[{"pid": 4242, "name": "node", "port": 3000, "cmdline": "node server.js"}]`
+pub fn parse_fixture(contents: &str) -> Result<Vec<DevProcess>, String> {
+    let value: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    let rows = value.as_array().ok_or("Expected a list of processes")?;
+    rows.iter()
+        .enumerate()
+        .map(|(i, row)| fixture_row(row).ok_or_else(|| format!("Row {} needs pid, name and port or socket", i)))
+        .collect()
+}
+
+/// Optional `env` objects of fixture rows, keyed by PID
+fn fixture_env(contents: &str) -> HashMap<u32, Vec<(String, String)>> {
+    let Ok(Value::Array(rows)) = serde_json::from_str(contents) else {
+        return HashMap::new();
+    };
+    rows.iter()
+        .filter_map(|row| {
+            let pid = row.get("pid")?.as_u64()? as u32;
+            let vars = row
+                .get("env")?
+                .as_object()?
+                .iter()
+                .map(|(k, v)| (k.clone(), v.as_str().map_or_else(|| v.to_string(), str::to_string)))
+                .collect();
+            Some((pid, vars))
+        })
+        .collect()
+}
+
+fn fixture_row(row: &Value) -> Option<DevProcess> {
+    let str_field = |key: &str| row.get(key).and_then(Value::as_str);
+    let u64_field = |key: &str| row.get(key).and_then(Value::as_u64);
+
+    let address: IpAddr = match str_field("address") {
+        Some(addr) => addr.parse().ok()?,
+        None => IpAddr::from([127, 0, 0, 1]),
+    };
+    let socket_path = str_field("socket").map(str::to_string);
+    let (port, protocol) = match socket_path {
+        Some(_) => (0, "UNIX".to_string()),
+        None => (
+            u64_field("port")? as u16,
+            str_field("protocol").unwrap_or("TCP").to_uppercase(),
+        ),
+    };
+    let mut p = DevProcess::new(u64_field("pid")? as u32, str_field("name")?, address, port, &protocol);
+    p.socket_path = socket_path;
+
+    p.cpu_percent = row.get("cpu").and_then(Value::as_f64).unwrap_or(0.0) as f32;
+    p.memory_bytes = u64_field("memory").unwrap_or(0);
+    p.memory_display = scanner::format_bytes(p.memory_bytes);
+    p.memory_detail = MemoryBreakdown {
+        pss: u64_field("pss"),
+        uss: u64_field("uss"),
+        swap: u64_field("swap"),
+        virtual_size: u64_field("virtual"),
+    };
+    p.uptime_secs = u64_field("uptime").unwrap_or(0);
+    p.uptime_display = scanner::format_uptime(p.uptime_secs);
+    p.cmdline = str_field("cmdline").unwrap_or_default().to_string();
+    p.cwd = str_field("cwd").map(PathBuf::from);
+    p.project = str_field("project").map(str::to_string);
+    p.framework = framework::from_cmdline(&p.cmdline);
+    p.user = str_field("user").map(str::to_string);
+    p.state = str_field("state").and_then(ProcState::parse).unwrap_or(ProcState::Sleeping);
+    p.connections = u64_field("connections").map(|c| c as u32);
+    p.accept_queue = u64_field("backlog").map(|backlog| AcceptQueue {
+        pending: u64_field("queue").unwrap_or(0) as u32,
+        backlog: backlog as u32,
+    });
+    Some(p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> ScanRequest {
+        ScanRequest {
+            tree: false,
+            probe_http: false,
+            show_all: false,
+            unix: false,
+            memory_detail: false,
+        }
+    }
+
+    #[test]
+    fn parses_fixture_rows() {
+        let json = r#"[
+            {"pid": 4242, "name": "node", "port": 3000, "cmdline": "next dev", "memory": 2048},
+            {"pid": 77, "name": "dnsmasq", "port": 53, "protocol": "udp", "address": "0.0.0.0"}
+        ]"#;
+        let rows = parse_fixture(json).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].framework, Some(Framework::Next));
+        assert_eq!(rows[0].memory_display, "2.0 KB");
+        assert_eq!(rows[1].protocol, "UDP");
+        assert_eq!(rows[1].address, IpAddr::from([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn demo_fixture_parses() {
+        let contents = include_str!("../fixtures/demo.json");
+        let rows = parse_fixture(contents).unwrap();
+        assert_eq!(rows.len(), 7);
+        assert!(rows[6].is_unix());
+        let env = fixture_env(contents);
+        assert!(env[&rows[0].pid].iter().any(|(k, _)| k == "PORT"));
+    }
+
+    #[test]
+    fn fake_stop_and_continue_flip_state() {
+        let source = FakeSource::new(vec![DevProcess::test("node", 3000)]);
+        source.signal(1000, Signal::Stop).unwrap();
+        assert_eq!(source.scan(request()).unwrap()[0].state, ProcState::Stopped);
+        source.signal(1000, Signal::Cont).unwrap();
+        assert_eq!(source.scan(request()).unwrap()[0].state, ProcState::Sleeping);
+        assert!(source.killed().is_empty());
+    }
+
+    #[test]
+    fn rejects_incomplete_fixture_rows() {
+        assert_eq!(
+            parse_fixture(r#"[{"pid": 1, "name": "node"}]"#).err(),
+            Some("Row 0 needs pid, name and port or socket".to_string())
+        );
+        assert!(parse_fixture(r#"{"pid": 1}"#).is_err());
+    }
+
+    #[test]
+    fn projects_are_cached_per_pid_and_cwd() {
+        let dir = std::env::temp_dir().join(format!("srvtop-project-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("package.json"), r#"{"name": "storefront"}"#).unwrap();
+        let mut p = DevProcess::test("node", 3000);
+        p.cwd = Some(dir.clone());
+        let mut processes = vec![p];
+
+        let source = LiveSource::new(DevFilter::default());
+        let mut state = source.state.lock().unwrap();
+        state.detect_projects(&mut processes);
+        std::fs::remove_file(dir.join("package.json")).unwrap();
+        state.detect_projects(&mut processes);
+        assert_eq!(processes[0].project.as_deref(), Some("storefront"));
+
+        // A restarted server is looked at again
+        processes[0].pid += 1;
+        state.detect_projects(&mut processes);
+        assert_eq!(processes[0].project, None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn fake_kill_removes_rows() {
+        let source = FakeSource::new(vec![DevProcess::test("node", 3000)]);
+        assert!(source.signal(1000, Signal::Term).is_ok());
+        assert!(source.signal(1000, Signal::Term).is_err());
+        assert!(source.scan(request()).unwrap().is_empty());
+        assert_eq!(source.killed(), vec![1000]);
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+
+use crate::scanner::DevProcess;
+
+/// Ancestors with these names are where a user started the server from, so
+/// the tree stops climbing there instead of walking up to init.
+const SESSION_ROOTS: &[&str] = &[
+    "bash", "zsh", "fish", "nu", "pwsh", "powershell", "powershell.exe", "cmd.exe",
+    "tmux", "tmux: server", "screen", "sshd", "login", "sudo", "su",
+    "systemd", "init", "launchd", "explorer.exe", "services.exe",
+    "code", "cursor", "idea", "containerd-shim", "containerd-shim-runc-v2",
+];
+
+/// Shells and package managers that launch a dev server without being one.
+/// Killing a tree climbs through them, so `npm run dev` doesn't outlive the
+/// server it started.
+const LAUNCH_WRAPPERS: &[&str] = &["sh", "dash", "npm", "npx", "yarn", "pnpm"];
+
+const MAX_DEPTH: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProcNode {
+    pub pid: u32,
+    pub name: String,
+    /// Distance from the process the node was collected for
+    pub depth: usize,
+}
+
+pub struct ProcessTable {
+    parents: HashMap<u32, u32>,
+    names: HashMap<u32, String>,
+    children: HashMap<u32, Vec<u32>>,
+}
+
+impl ProcessTable {
+    pub fn from_system(system: &System) -> Self {
+        Self::from_entries(system.processes().iter().map(|(pid, proc)| {
+            (
+                pid.as_u32(),
+                proc.parent().map(|p| p.as_u32()),
+                proc.name().to_string_lossy().to_string(),
+            )
+        }))
+    }
+
+    /// Reads the parent and name of every process into `system` without
+    /// touching CPU or memory, so a dedicated `System` keeps this cheap
+    pub fn snapshot(system: &mut System) -> Self {
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing(),
+        );
+        Self::from_system(system)
+    }
+
+    pub fn from_entries(entries: impl IntoIterator<Item = (u32, Option<u32>, String)>) -> Self {
+        let mut parents = HashMap::new();
+        let mut names = HashMap::new();
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (pid, parent, name) in entries {
+            if let Some(parent) = parent {
+                parents.insert(pid, parent);
+                children.entry(parent).or_default().push(pid);
+            }
+            names.insert(pid, name);
+        }
+        for kids in children.values_mut() {
+            kids.sort_unstable();
+        }
+        Self { parents, names, children }
+    }
+
+    pub fn parent(&self, pid: u32) -> Option<u32> {
+        self.parents.get(&pid).copied()
+    }
+
+    /// Launcher chain above `pid`, root first, ending at its direct parent
+    pub fn ancestors(&self, pid: u32) -> Vec<ProcNode> {
+        let mut chain = Vec::new();
+        let mut current = pid;
+        while let Some(parent) = self.parent(current) {
+            if parent <= 1 || chain.len() >= MAX_DEPTH {
+                break;
+            }
+            let name = self.names.get(&parent).cloned().unwrap_or_default();
+            if SESSION_ROOTS.contains(&name.to_lowercase().as_str()) {
+                break;
+            }
+            chain.push(ProcNode {
+                pid: parent,
+                name,
+                depth: chain.len() + 1,
+            });
+            current = parent;
+        }
+        chain.reverse();
+        chain
+    }
+
+    /// Every process below `pid`, depth-first
+    pub fn descendants(&self, pid: u32) -> Vec<ProcNode> {
+        let mut out = Vec::new();
+        self.collect_descendants(pid, 1, &mut out);
+        out
+    }
+
+    fn collect_descendants(&self, pid: u32, depth: usize, out: &mut Vec<ProcNode>) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+        for &child in self.children.get(&pid).map(Vec::as_slice).unwrap_or_default() {
+            out.push(ProcNode {
+                pid: child,
+                name: self.names.get(&child).cloned().unwrap_or_default(),
+                depth,
+            });
+            self.collect_descendants(child, depth + 1, out);
+        }
+    }
+
+    /// The tree rooted at `pid`'s highest ancestor that is still a dev
+    /// process (per `is_dev`, given a name) or a launch wrapper, deepest
+    /// processes first so children are signalled before the parent can
+    /// respawn them. The root comes last. Climbing stops at the session root
+    /// or the first other ancestor, so a listener under dockerd, supervisord
+    /// or pm2 never takes its daemon with it.
+    pub fn kill_order(&self, pid: u32, is_dev: impl Fn(&str) -> bool) -> Vec<ProcNode> {
+        let root = self
+            .ancestors(pid)
+            .into_iter()
+            .rev()
+            .take_while(|n| is_dev(&n.name) || is_launch_wrapper(&n.name))
+            .last()
+            .map_or(pid, |n| n.pid);
+        let mut nodes = self.descendants(root);
+        nodes.sort_by_key(|n| std::cmp::Reverse(n.depth));
+        nodes.push(ProcNode {
+            pid: root,
+            name: self.names.get(&root).cloned().unwrap_or_default(),
+            depth: 0,
+        });
+        nodes
+    }
+}
+
+/// npm renames itself after its arguments, e.g. `npm run dev`
+fn is_launch_wrapper(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.split_whitespace()
+        .next()
+        .is_some_and(|command| LAUNCH_WRAPPERS.contains(&command))
+}
+
+/// Index of the closest row whose PID is an ancestor of `processes[index]`
+pub fn listening_parent(processes: &[DevProcess], index: usize) -> Option<usize> {
+    processes[index].ancestors.iter().rev().find_map(|node| {
+        processes
+            .iter()
+            .position(|p| p.pid == node.pid && p.pid != processes[index].pid)
+    })
+}
+
+/// Reorders rows so listeners started by another listener follow it,
+/// keeping the existing sort order among siblings.
+pub fn arrange(processes: Vec<DevProcess>) -> Vec<DevProcess> {
+    let parents: Vec<Option<usize>> = (0..processes.len())
+        .map(|i| listening_parent(&processes, i))
+        .collect();
+
+    let mut order = Vec::with_capacity(processes.len());
+    for (i, parent) in parents.iter().enumerate() {
+        if parent.is_none() {
+            push_subtree(i, &parents, &mut order);
+        }
+    }
+
+    let mut slots: Vec<Option<DevProcess>> = processes.into_iter().map(Some).collect();
+    order.into_iter().filter_map(|i| slots[i].take()).collect()
+}
+
+fn push_subtree(index: usize, parents: &[Option<usize>], order: &mut Vec<usize>) {
+    if order.contains(&index) {
+        return;
+    }
+    order.push(index);
+    for (child, parent) in parents.iter().enumerate() {
+        if *parent == Some(index) {
+            push_subtree(child, parents, order);
+        }
+    }
+}
+
+/// How many listening rows sit above `processes[index]` in the tree
+pub fn nesting_depth(processes: &[DevProcess], index: usize) -> usize {
+    let mut depth = 0;
+    let mut current = index;
+    while let Some(parent) = listening_parent(processes, current) {
+        depth += 1;
+        current = parent;
+        if depth > processes.len() {
+            break;
+        }
+    }
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // zsh(100) -> npm(200) -> sh(300) -> node(400) -> esbuild(500)
+    //                                               -> node(410) -> esbuild(510)
+    fn table() -> ProcessTable {
+        ProcessTable::from_entries(vec![
+            (1, None, "systemd".to_string()),
+            (100, Some(1), "zsh".to_string()),
+            (200, Some(100), "npm".to_string()),
+            (300, Some(200), "sh".to_string()),
+            (400, Some(300), "node".to_string()),
+            (410, Some(400), "node".to_string()),
+            (500, Some(400), "esbuild".to_string()),
+            (510, Some(410), "esbuild".to_string()),
+        ])
+    }
+
+    fn row(pid: u32, port: u16, table: &ProcessTable) -> DevProcess {
+        DevProcess {
+            pid,
+            ancestors: table.ancestors(pid),
+            ..DevProcess::test("node", port)
+        }
+    }
+
+    #[test]
+    fn ancestors_stop_at_session_root() {
+        let names: Vec<_> = table().ancestors(400).into_iter().map(|n| n.name).collect();
+        assert_eq!(names, vec!["npm", "sh"]);
+    }
+
+    #[test]
+    fn descendants_are_depth_first() {
+        let nodes = table().descendants(400);
+        let pids: Vec<_> = nodes.iter().map(|n| (n.pid, n.depth)).collect();
+        assert_eq!(pids, vec![(410, 1), (510, 2), (500, 1)]);
+    }
+
+    fn is_dev(name: &str) -> bool {
+        crate::filter::DevFilter::default().matches_name(name)
+    }
+
+    #[test]
+    fn kill_order_climbs_through_dev_parents_and_wrappers() {
+        let order = table().kill_order(410, is_dev);
+        let pids: Vec<_> = order.iter().map(|n| n.pid).collect();
+        assert_eq!(pids, vec![510, 410, 500, 400, 300, 200]);
+        assert_eq!(order.last().unwrap().name, "npm");
+    }
+
+    #[test]
+    fn kill_order_reaches_the_npm_launcher() {
+        let table = ProcessTable::from_entries(vec![
+            (1, None, "systemd".to_string()),
+            (100, Some(1), "bash".to_string()),
+            (200, Some(100), "npm run dev".to_string()),
+            (300, Some(200), "sh".to_string()),
+            (400, Some(300), "node".to_string()),
+            (500, Some(400), "esbuild".to_string()),
+        ]);
+        let pids: Vec<_> = table.kill_order(500, is_dev).iter().map(|n| n.pid).collect();
+        assert_eq!(pids, vec![500, 400, 300, 200]);
+    }
+
+    #[test]
+    fn kill_order_leaves_the_daemon_alone() {
+        let table = ProcessTable::from_entries(vec![
+            (1, None, "systemd".to_string()),
+            (700, Some(1), "dockerd".to_string()),
+            (710, Some(700), "docker-proxy".to_string()),
+            (720, Some(700), "docker-proxy".to_string()),
+            (730, Some(700), "containerd".to_string()),
+        ]);
+        let pids: Vec<_> = table.kill_order(710, is_dev).iter().map(|n| n.pid).collect();
+        assert_eq!(pids, vec![710]);
+    }
+
+    #[test]
+    fn arrange_nests_listening_children() {
+        let table = table();
+        let rows = arrange(vec![row(410, 3001, &table), row(999, 8080, &table), row(400, 3000, &table)]);
+        let pids: Vec<_> = rows.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![999, 400, 410]);
+        assert_eq!(nesting_depth(&rows, 2), 1);
+        assert_eq!(nesting_depth(&rows, 1), 0);
+    }
+
+    #[test]
+    fn snapshot_knows_own_parent() {
+        let pid = std::process::id();
+        let mut system = System::new();
+        let table = ProcessTable::snapshot(&mut system);
+        assert!(table.parent(pid).is_some());
+        assert!(system.process(sysinfo::Pid::from_u32(pid)).unwrap().cmd().is_empty());
+    }
+}
@@ -1,15 +1,23 @@
+use std::net::IpAddr;
+
 use ratatui::{
     layout::{Alignment, Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
     symbols::border,
-    text::{Line, Span},
+    text::{Line, Span, Text},
     widgets::{
-        Block, Borders, Cell, Clear, Paragraph, Row, Scrollbar, ScrollbarOrientation, Table,
+        Block, Borders, Cell, Clear, Paragraph, Row, Scrollbar, ScrollbarOrientation, Table, Wrap,
     },
     Frame,
 };
 
-use crate::app::{App, OwlMood, SortColumn, SortDirection};
+use crate::app::{App, ContainerFilter, CpuMode, MemoryMetric, OwlMood, ProtoFilter, SortColumn, SortDirection};
+use crate::environ::{self, EnvView};
+use crate::inspect::PortReport;
+use crate::netstat::TcpState;
+use crate::scanner::{self, DevProcess, Exposure, PortBinding, ProcState};
+use crate::source::Signal;
+use crate::tree::{self, ProcNode};
 
 const BORDER_SET: border::Set = border::Set {
     top_left: "╭",
@@ -22,8 +30,6 @@ const BORDER_SET: border::Set = border::Set {
     horizontal_bottom: "─",
 };
 
-const NAME_MAX_WIDTH: usize = 20;
-
 pub fn draw(frame: &mut Frame, app: &mut App) {
     let chunks = Layout::vertical([
         Constraint::Length(2),
@@ -35,9 +41,25 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
 
     draw_header(frame, app, chunks[1]);
     draw_owl(frame, app, chunks[0], chunks[1]);
-    draw_table(frame, app, chunks[2]);
+    if app.show_memory_detail {
+        let [table_area, pane_area] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(40)]).areas(chunks[2]);
+        draw_table(frame, app, table_area);
+        draw_memory_detail(frame, app, pane_area);
+    } else {
+        draw_table(frame, app, chunks[2]);
+    }
     draw_footer(frame, app, chunks[3]);
 
+    if let Some(ref view) = app.env_view {
+        draw_env(frame, view);
+    }
+    if let Some(ref report) = app.port_report {
+        draw_port_report(frame, report);
+    }
+    if app.show_help {
+        draw_help(frame);
+    }
     if app.show_kill_confirm {
         draw_kill_confirm(frame, app);
     }
@@ -163,7 +185,13 @@ fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
     let remaining = app.tick_rate_secs.saturating_sub(elapsed);
     let countdown = format!(" \u{27f3} {}s ", remaining);
 
-    let header = Line::from(vec![
+    let exposed = app
+        .processes
+        .iter()
+        .filter(|p| p.exposure() != Exposure::Local)
+        .count();
+
+    let mut spans = vec![
         Span::styled(
             " srvtop ",
             Style::default()
@@ -185,8 +213,114 @@ fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw(" "),
-        Span::styled(countdown, Style::default().fg(Color::DarkGray)),
-    ]);
+    ];
+
+    if app.mine_only {
+        spans.push(Span::styled(
+            " MINE ",
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightBlue)
+                .add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::raw(" "));
+    }
+
+    if app.show_unix && app.filter_proto != Some(ProtoFilter::Unix) {
+        spans.push(Span::styled(
+            " +UNIX ",
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightMagenta)
+                .add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::raw(" "));
+    }
+
+    let containers = match app.container_filter {
+        ContainerFilter::Show => None,
+        ContainerFilter::Hide => Some(" HOST "),
+        ContainerFilter::Only => Some(" CONTAINERS "),
+    };
+    if let Some(label) = containers {
+        spans.push(Span::styled(
+            label,
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightBlue)
+                .add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::raw(" "));
+    }
+
+    if let Some(proto) = app.filter_proto {
+        spans.push(Span::styled(
+            format!(" {} ", proto.label()),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::raw(" "));
+    }
+
+    if let Some(cpu) = app.machine_cpu {
+        let color = if cpu.usage >= 80.0 {
+            Color::Red
+        } else if cpu.usage >= 50.0 {
+            Color::Yellow
+        } else {
+            Color::Green
+        };
+        spans.push(Span::styled(machine_cpu_gauge(cpu.usage), Style::default().fg(color)));
+    }
+
+    if app.worker.busy() {
+        spans.push(Span::styled(
+            " scanning\u{2026} ",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::ITALIC),
+        ));
+    } else {
+        spans.push(Span::styled(countdown, Style::default().fg(Color::DarkGray)));
+    }
+
+    if app.debug {
+        spans.push(Span::styled(
+            format!(
+                " scan {:.1}ms \u{00b7} {} pids ",
+                app.scan_cost.as_secs_f64() * 1000.0,
+                app.tracked
+            ),
+            Style::default().fg(Color::Magenta),
+        ));
+    }
+
+    let stopped = app
+        .processes
+        .iter()
+        .filter(|p| p.state == ProcState::Stopped)
+        .count();
+    if stopped > 0 {
+        spans.push(Span::styled(
+            format!(" \u{23f8} {} stopped, Z resumes ", stopped),
+            Style::default()
+                .fg(Color::Red)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    if exposed > 0 {
+        spans.push(Span::styled(
+            format!(" \u{25b2} {} exposed ", exposed),
+            Style::default()
+                .fg(Color::Red)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    let header = Line::from(spans);
 
     let block = Block::default()
         .borders(Borders::ALL)
@@ -207,7 +341,7 @@ fn sort_indicator(app: &App, col: SortColumn) -> &'static str {
     }
 }
 
-fn port_badge(port: u16) -> Cell<'static> {
+fn port_style(port: u16, protocol: &str) -> Style {
     let color = match port {
         3000..=3999 => Color::Green,
         4000..=4999 => Color::Cyan,
@@ -218,20 +352,130 @@ fn port_badge(port: u16) -> Cell<'static> {
         27017 => Color::Green,
         _ => Color::DarkGray,
     };
-    Cell::from(format!(" :{} ", port)).style(
+    // Unix sockets have no port to colour by
+    if protocol.eq_ignore_ascii_case("UNIX") {
+        return Style::default()
+            .fg(Color::LightMagenta)
+            .add_modifier(Modifier::BOLD);
+    }
+    // UDP badges are drawn hollow so datagram services stand apart from TCP
+    if protocol.eq_ignore_ascii_case("UDP") {
+        Style::default()
+            .fg(color)
+            .add_modifier(Modifier::BOLD | Modifier::ITALIC)
+    } else {
         Style::default()
             .fg(Color::Black)
             .bg(color)
-            .add_modifier(Modifier::BOLD),
-    )
+            .add_modifier(Modifier::BOLD)
+    }
+}
+
+fn port_badge(process: &DevProcess, expanded: bool) -> Cell<'static> {
+    let mut lines = vec![Line::from(Span::styled(
+        format!(" {} ", process.port_label()),
+        port_style(process.port, &process.protocol),
+    ))];
+    if expanded {
+        lines.extend(process.bindings().map(|b| {
+            Line::from(vec![
+                Span::styled("\u{2514} ", Style::default().fg(Color::DarkGray)),
+                Span::styled(format!("{} ", b.label()), port_style(b.port, &b.protocol)),
+            ])
+        }));
+    }
+    Cell::from(Text::from(lines))
+}
+
+fn address_span(address: IpAddr) -> Span<'static> {
+    let text = scanner::format_address(address);
+    match scanner::exposure(address) {
+        Exposure::Local => Span::styled(format!("  {}", text), Style::default().fg(Color::DarkGray)),
+        Exposure::Interface => {
+            Span::styled(format!("\u{25b2} {}", text), Style::default().fg(Color::Yellow))
+        }
+        Exposure::Everywhere => Span::styled(
+            format!("\u{25b2} {}", text),
+            Style::default()
+                .fg(Color::Red)
+                .add_modifier(Modifier::BOLD),
+        ),
+    }
+}
+
+/// IP address, or the directory holding a Unix socket
+fn binding_span(binding: &PortBinding) -> Span<'static> {
+    match binding.socket_path {
+        Some(ref path) => {
+            let dir = path.rsplit_once('/').map_or("", |(dir, _)| dir);
+            Span::styled(
+                format!("  {}", if dir.is_empty() { "/" } else { dir }),
+                Style::default().fg(Color::DarkGray),
+            )
+        }
+        None => address_span(binding.address),
+    }
+}
+
+fn address_cell(process: &DevProcess, expanded: bool) -> Cell<'static> {
+    // Collapsed rows show the most reachable address so exposure isn't hidden
+    let widest = process
+        .bindings()
+        .find(|b| scanner::exposure(b.address) == process.exposure());
+    let mut lines = vec![Line::from(widest.map_or_else(|| address_span(process.address), |b| binding_span(&b)))];
+    if expanded {
+        lines.extend(process.bindings().map(|b| Line::from(binding_span(&b))));
+    }
+    Cell::from(Text::from(lines))
+}
+
+fn proto_label(binding: &PortBinding) -> String {
+    if binding.socket_path.is_some() {
+        binding.protocol.clone()
+    } else {
+        format!("{} {}", binding.protocol, binding.family.label())
+    }
+}
+
+fn proto_cell(process: &DevProcess, expanded: bool) -> Cell<'static> {
+    let mut lines: Vec<Line> = process.bindings().take(1).map(|b| Line::from(proto_label(&b))).collect();
+    if expanded {
+        lines.extend(process.bindings().map(|b| Line::from(proto_label(&b))));
+    }
+    Cell::from(Text::from(lines)).style(Style::default().fg(Color::DarkGray))
 }
 
-fn cpu_bar(percent: f32) -> String {
+/// `full` is the percentage a full block stands for
+fn cpu_bar(percent: f32, full: f32) -> String {
     let blocks = [' ', '\u{2581}', '\u{2582}', '\u{2583}', '\u{2584}', '\u{2585}', '\u{2586}', '\u{2587}', '\u{2588}'];
-    let idx = ((percent / 100.0) * 8.0).round().clamp(0.0, 8.0) as usize;
+    let idx = ((percent / full) * 8.0).round().clamp(0.0, 8.0) as usize;
     format!("{} {:.1}%", blocks[idx], percent)
 }
 
+/// Colour for a CPU bar filled to `fill` of the machine. Thresholds are in
+/// cores, so one pegged thread stands out whichever way CPU% is shown.
+fn cpu_style(fill: f32, cores: usize) -> Style {
+    let cores_busy = fill * cores as f32;
+    if cores_busy > 0.5 {
+        Style::default()
+            .fg(Color::Red)
+            .add_modifier(Modifier::BOLD)
+    } else if cores_busy > 0.2 {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default().fg(Color::DarkGray)
+    }
+}
+
+/// ` CPU ▕███▌    ▏ 44% ` for the whole machine
+fn machine_cpu_gauge(usage: f32) -> String {
+    let width = 8;
+    let eighths = ((usage / 100.0).clamp(0.0, 1.0) * (width * 8) as f32).round() as usize;
+    let partial = ["", "\u{258f}", "\u{258e}", "\u{258d}", "\u{258c}", "\u{258b}", "\u{258a}", "\u{2589}"];
+    let bar = "\u{2588}".repeat(eighths / 8) + partial[eighths % 8];
+    format!(" CPU \u{2595}{:<width$}\u{258f} {:.0}% ", bar, usage, width = width)
+}
+
 fn memory_bar(bytes: u64, max_bytes: u64, display: &str) -> String {
     let bar_width = 5;
     let ratio = if max_bytes > 0 {
@@ -249,17 +493,210 @@ fn memory_bar(bytes: u64, max_bytes: u64, display: &str) -> String {
     )
 }
 
-fn truncate_name(name: &str, max_width: usize) -> String {
-    if name.chars().count() > max_width {
-        let truncated: String = name.chars().take(max_width - 1).collect();
-        format!("{}\u{2026}", truncated)
+fn io_bar(rate: Option<u64>, max_rate: u64) -> String {
+    match rate {
+        Some(rate) => {
+            let display = scanner::format_rate(rate);
+            // Reuse the memory gauge, scaled to the busiest process on screen
+            memory_bar(rate, max_rate, &display)
+        }
+        None => format!("{} -", "\u{2591}".repeat(5)),
+    }
+}
+
+fn io_style(rate: Option<u64>) -> Style {
+    match rate {
+        Some(r) if r >= 10 * 1024 * 1024 => Style::default()
+            .fg(Color::Red)
+            .add_modifier(Modifier::BOLD),
+        Some(r) if r >= 1024 * 1024 => Style::default().fg(Color::Yellow),
+        Some(r) if r > 0 => Style::default().fg(Color::Magenta),
+        _ => Style::default().fg(Color::DarkGray),
+    }
+}
+
+/// The S column: a state glyph, red when the server can't answer
+fn state_cell(state: ProcState) -> Cell<'static> {
+    let style = match state {
+        ProcState::Running => Style::default().fg(Color::Green),
+        ProcState::Stopped => Style::default()
+            .fg(Color::Red)
+            .add_modifier(Modifier::BOLD),
+        ProcState::Zombie => Style::default().fg(Color::Red),
+        ProcState::DiskWait => Style::default().fg(Color::Yellow),
+        ProcState::Sleeping | ProcState::Unknown => Style::default().fg(Color::DarkGray),
+    };
+    Cell::from(state.glyph()).style(style)
+}
+
+/// Share of the backlog at which QUEUE turns red
+const QUEUE_WARN_FILL: f64 = 0.8;
+
+/// Accept queue as "pending/backlog"; red once the server falls behind
+/// enough that new connections are about to be dropped
+fn queue_cell(process: &DevProcess) -> Cell<'static> {
+    let Some(queue) = process.accept_queue else {
+        return Cell::from("-").style(Style::default().fg(Color::DarkGray));
+    };
+    let style = if queue.fill() >= QUEUE_WARN_FILL {
+        Style::default()
+            .fg(Color::Red)
+            .add_modifier(Modifier::BOLD)
+    } else if queue.pending > 0 {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default().fg(Color::DarkGray)
+    };
+    Cell::from(format!("{}/{}", queue.pending, queue.backlog)).style(style)
+}
+
+fn fd_cell(process: &DevProcess, warn_ratio: f64) -> Cell<'static> {
+    let text = match (process.fds, process.fd_limit) {
+        (Some(fds), Some(limit)) => format!("{}/{}", fds, limit),
+        (Some(fds), None) => fds.to_string(),
+        _ => "-".to_string(),
+    };
+    let style = match process.fd_usage() {
+        Some(usage) if usage >= warn_ratio => Style::default()
+            .fg(Color::Red)
+            .add_modifier(Modifier::BOLD),
+        Some(usage) if usage >= warn_ratio / 2.0 => Style::default().fg(Color::Yellow),
+        _ => Style::default().fg(Color::DarkGray),
+    };
+    Cell::from(text).style(style)
+}
+
+fn command_display(process: &DevProcess, offset: usize) -> String {
+    let cmd = if process.cmdline.is_empty() {
+        match process.exe {
+            Some(ref exe) => format!("[{}]", exe.display()),
+            None => format!("[{}]", process.name),
+        }
     } else {
-        name.to_string()
+        process.cmdline.clone()
+    };
+    if offset == 0 {
+        return cmd;
+    }
+    let rest: String = cmd.chars().skip(offset).collect();
+    format!("\u{2026}{}", rest)
+}
+
+const TREE_MAX_CHILDREN: usize = 6;
+
+/// Docker-published ports read better under the container's name than
+/// under `docker-proxy`
+fn display_name(process: &DevProcess) -> &str {
+    process
+        .container
+        .as_ref()
+        .and_then(|c| c.name.as_deref())
+        .unwrap_or(&process.name)
+}
+
+fn name_text(app: &App, index: usize) -> Text<'static> {
+    let p = &app.processes[index];
+    let dim = Style::default().fg(Color::DarkGray);
+
+    let mut leaf = Vec::new();
+    let mut indent = String::new();
+    if app.tree_view {
+        let depth = tree::nesting_depth(&app.processes, index);
+        // Nested listeners only show the launchers below their listening parent
+        let chain_start = tree::listening_parent(&app.processes, index)
+            .and_then(|parent| {
+                let parent_pid = app.processes[parent].pid;
+                p.ancestors.iter().position(|n| n.pid == parent_pid)
+            })
+            .map(|pos| pos + 1)
+            .unwrap_or(0);
+        indent = "  ".repeat(depth);
+        if depth > 0 {
+            leaf.push(Span::styled(format!("{}\u{2514} ", &indent[2..]), dim));
+        }
+        for node in &p.ancestors[chain_start..] {
+            leaf.push(Span::styled(format!("{} \u{203a} ", node.name), dim));
+        }
+    }
+
+    match p.framework {
+        Some(fw) => {
+            leaf.push(Span::styled("\u{25cf} ", Style::default().fg(fw.color())));
+            leaf.push(Span::raw(format!("{} ", display_name(p))));
+            leaf.push(Span::styled(
+                fw.label(),
+                Style::default()
+                    .fg(fw.color())
+                    .add_modifier(Modifier::ITALIC),
+            ));
+        }
+        None => leaf.push(Span::raw(format!("\u{25cf} {}", display_name(p)))),
+    }
+
+    if let Some(ref container) = p.container {
+        let badge = match container.short_id() {
+            Some(id) => format!(" {}:{} ", container.runtime.label(), id),
+            None => format!(" {} ", container.runtime.label()),
+        };
+        leaf.push(Span::raw(" "));
+        leaf.push(Span::styled(
+            badge,
+            Style::default().fg(Color::Black).bg(Color::LightBlue),
+        ));
     }
+
+    let mut lines = vec![Line::from(leaf)];
+    if app.tree_view {
+        // Listening descendants get rows of their own; list the rest here
+        let children: Vec<_> = p
+            .descendants
+            .iter()
+            .filter(|n| !app.processes.iter().any(|row| row.pid == n.pid))
+            .collect();
+        for node in children.iter().take(TREE_MAX_CHILDREN) {
+            lines.push(Line::from(Span::styled(
+                format!("{}{}\u{2514} {} {}", indent, "  ".repeat(node.depth), node.name, node.pid),
+                dim,
+            )));
+        }
+        if children.len() > TREE_MAX_CHILDREN {
+            lines.push(Line::from(Span::styled(
+                format!("{}  \u{2026} +{} more", indent, children.len() - TREE_MAX_CHILDREN),
+                dim,
+            )));
+        }
+    }
+    Text::from(lines)
+}
+
+fn location_line(process: &DevProcess) -> Line<'static> {
+    let label_style = Style::default().fg(Color::DarkGray);
+    let value_style = Style::default().fg(Color::Gray);
+    let unknown = || "?".to_string();
+    let mut spans = Vec::new();
+    if let Some(image) = process.container.as_ref().and_then(|c| c.image.as_ref()) {
+        spans.push(Span::styled(" image ", label_style));
+        spans.push(Span::styled(image.clone(), value_style));
+        spans.push(Span::raw(" "));
+    }
+    spans.extend([
+        Span::styled(" cwd ", label_style),
+        Span::styled(
+            process.cwd.as_ref().map(|p| p.display().to_string()).unwrap_or_else(unknown),
+            value_style,
+        ),
+        Span::styled("  exe ", label_style),
+        Span::styled(
+            process.exe.as_ref().map(|p| p.display().to_string()).unwrap_or_else(unknown),
+            value_style,
+        ),
+        Span::raw(" "),
+    ]);
+    Line::from(spans)
 }
 
 fn draw_table(frame: &mut Frame, app: &mut App, area: Rect) {
-    let block = Block::default()
+    let mut block = Block::default()
         .borders(Borders::ALL)
         .border_set(BORDER_SET)
         .border_style(Style::default().fg(Color::DarkGray))
@@ -269,6 +706,9 @@ fn draw_table(frame: &mut Frame, app: &mut App, area: Rect) {
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
         ));
+    if let Some(p) = app.selected_process() {
+        block = block.title_bottom(location_line(p));
+    }
 
     if app.processes.is_empty() {
         let msg = if app.show_all {
@@ -287,19 +727,53 @@ fn draw_table(frame: &mut Frame, app: &mut App, area: Rect) {
     let max_memory = app
         .processes
         .iter()
-        .map(|p| p.memory_bytes)
+        .filter_map(|p| app.memory_metric.of(p))
         .max()
         .unwrap_or(1);
 
-    let header_cells = [
+    let max_read = app.processes.iter().filter_map(|p| p.io_read_rate).max().unwrap_or(0);
+    let max_write = app.processes.iter().filter_map(|p| p.io_write_rate).max().unwrap_or(0);
+    // Idle queues are the norm, so QUEUE only takes room once one backs up
+    let show_queue = app
+        .processes
+        .iter()
+        .any(|p| p.accept_queue.is_some_and(|q| q.pending > 0));
+
+    let mut header_cells = vec![
         format!("PID{}", sort_indicator(app, SortColumn::Pid)),
+        "S".to_string(),
         format!("NAME{}", sort_indicator(app, SortColumn::Name)),
+        format!("PROJECT{}", sort_indicator(app, SortColumn::Project)),
+        "USER".to_string(),
         format!("PORT{}", sort_indicator(app, SortColumn::Port)),
+        "ADDR".to_string(),
         format!("PROTO{}", sort_indicator(app, SortColumn::Proto)),
-        format!("CPU%{}", sort_indicator(app, SortColumn::Cpu)),
-        format!("MEMORY{}", sort_indicator(app, SortColumn::Memory)),
-        format!("UPTIME{}", sort_indicator(app, SortColumn::Uptime)),
+        format!("CONN{}", sort_indicator(app, SortColumn::Conn)),
     ];
+    if show_queue {
+        header_cells.push("QUEUE".to_string());
+    }
+    header_cells.extend([
+        match app.cpu_mode {
+            CpuMode::PerCore => format!("CPU%{}", sort_indicator(app, SortColumn::Cpu)),
+            CpuMode::Machine => format!("CPU%/{}{}", app.cores(), sort_indicator(app, SortColumn::Cpu)),
+        },
+        format!("{}{}", app.memory_metric.column(), sort_indicator(app, SortColumn::Memory)),
+    ]);
+    if app.show_io {
+        header_cells.push("READ".to_string());
+        header_cells.push("WRITE".to_string());
+    }
+    if app.show_fds {
+        header_cells.push("THR".to_string());
+        header_cells.push("FDS".to_string());
+    }
+    header_cells.push(format!("UPTIME{}", sort_indicator(app, SortColumn::Uptime)));
+    header_cells.push(if app.cmd_scroll > 0 {
+        format!("CMD +{}", app.cmd_scroll)
+    } else {
+        "CMD".to_string()
+    });
 
     let header = Row::new(header_cells.iter().map(|h| {
         Cell::from(h.as_str()).style(
@@ -325,10 +799,7 @@ fn draw_table(frame: &mut Frame, app: &mut App, area: Rect) {
                 Style::default()
             };
 
-            let name_display = format!(
-                "\u{25cf} {}",
-                truncate_name(&p.name, NAME_MAX_WIDTH)
-            );
+            let name_display = name_text(app, i);
             let name_style = if selected {
                 Style::default()
                     .fg(Color::Cyan)
@@ -337,50 +808,111 @@ fn draw_table(frame: &mut Frame, app: &mut App, area: Rect) {
                 Style::default().fg(Color::White)
             };
 
-            let cpu_style = if p.cpu_percent > 50.0 {
-                Style::default()
-                    .fg(Color::Red)
-                    .add_modifier(Modifier::BOLD)
-            } else if p.cpu_percent > 20.0 {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default().fg(Color::DarkGray)
-            };
+            let expanded = app.grouped && app.expanded.contains(&p.pid) && !p.other_ports.is_empty();
+            let port_lines = if expanded { 2 + p.other_ports.len() } else { 1 };
+            let height = port_lines.max(name_display.lines.len()) as u16;
 
-            Row::new(vec![
+            let (cpu, cpu_full) = app.cpu_shown(p);
+            let cpu_style = cpu_style(cpu / cpu_full, app.cores());
+
+            let mut cells = vec![
                 Cell::from(format!(" {} ", p.pid)).style(Style::default().fg(Color::DarkGray)),
+                state_cell(p.state),
                 Cell::from(name_display).style(name_style),
-                port_badge(p.port),
-                Cell::from(p.protocol.clone()).style(Style::default().fg(Color::DarkGray)),
-                Cell::from(cpu_bar(p.cpu_percent)).style(cpu_style),
+                match p.project {
+                    Some(ref project) => Cell::from(project.clone())
+                        .style(Style::default().fg(Color::LightBlue)),
+                    None => Cell::from("-").style(Style::default().fg(Color::DarkGray)),
+                },
                 {
-                    let mem_ratio = if max_memory > 0 { p.memory_bytes as f64 / max_memory as f64 } else { 0.0 };
-                    let mem_color = if mem_ratio > 0.8 {
-                        Color::Red
-                    } else if mem_ratio > 0.5 {
-                        Color::Yellow
+                    let user = p.user.clone().unwrap_or_else(|| "?".to_string());
+                    let user_style = if app.is_mine(p) {
+                        Style::default().fg(Color::DarkGray)
                     } else {
-                        Color::Cyan
+                        Style::default().fg(Color::LightMagenta)
                     };
-                    Cell::from(memory_bar(p.memory_bytes, max_memory, &p.memory_display))
-                        .style(Style::default().fg(mem_color))
+                    Cell::from(user).style(user_style)
+                },
+                port_badge(p, expanded),
+                address_cell(p, expanded),
+                proto_cell(p, expanded),
+                match p.connections {
+                    Some(0) => Cell::from("0").style(Style::default().fg(Color::DarkGray)),
+                    Some(n) => Cell::from(n.to_string()).style(
+                        Style::default()
+                            .fg(Color::Green)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    None => Cell::from("-").style(Style::default().fg(Color::DarkGray)),
+                },
+            ];
+            if show_queue {
+                cells.push(queue_cell(p));
+            }
+            cells.extend([
+                Cell::from(cpu_bar(cpu, cpu_full)).style(cpu_style),
+                match app.memory_metric.of(p) {
+                    Some(bytes) => {
+                        let mem_ratio = if max_memory > 0 { bytes as f64 / max_memory as f64 } else { 0.0 };
+                        let mem_color = if mem_ratio > 0.8 {
+                            Color::Red
+                        } else if mem_ratio > 0.5 {
+                            Color::Yellow
+                        } else {
+                            Color::Cyan
+                        };
+                        Cell::from(memory_bar(bytes, max_memory, &scanner::format_bytes(bytes)))
+                            .style(Style::default().fg(mem_color))
+                    }
+                    None => Cell::from("-").style(Style::default().fg(Color::DarkGray)),
                 },
+            ]);
+            if app.show_io {
+                cells.push(Cell::from(io_bar(p.io_read_rate, max_read)).style(io_style(p.io_read_rate)));
+                cells.push(Cell::from(io_bar(p.io_write_rate, max_write)).style(io_style(p.io_write_rate)));
+            }
+            if app.show_fds {
+                cells.push(
+                    Cell::from(p.threads.map_or("-".to_string(), |t| t.to_string()))
+                        .style(Style::default().fg(Color::DarkGray)),
+                );
+                cells.push(fd_cell(p, app.fd_warn_ratio));
+            }
+            cells.push(
                 Cell::from(p.uptime_display.clone())
                     .style(Style::default().fg(Color::DarkGray)),
-            ])
-            .style(style)
+            );
+            cells.push(
+                Cell::from(command_display(p, app.cmd_scroll))
+                    .style(Style::default().fg(Color::Gray)),
+            );
+
+            Row::new(cells).height(height).style(style)
         })
         .collect();
 
-    let widths = [
+    let mut widths = vec![
         Constraint::Length(10),
-        Constraint::Min(15),
+        Constraint::Length(2),
+        Constraint::Length(24),
+        Constraint::Length(18),
+        Constraint::Length(10),
+        Constraint::Length(11),
+        Constraint::Length(18),
         Constraint::Length(9),
         Constraint::Length(6),
-        Constraint::Length(12),
-        Constraint::Length(18),
-        Constraint::Length(8),
     ];
+    if show_queue {
+        widths.push(Constraint::Length(10));
+    }
+    widths.extend([Constraint::Length(12), Constraint::Length(18)]);
+    if app.show_io {
+        widths.extend([Constraint::Length(18), Constraint::Length(18)]);
+    }
+    if app.show_fds {
+        widths.extend([Constraint::Length(5), Constraint::Length(13)]);
+    }
+    widths.extend([Constraint::Length(8), Constraint::Min(20)]);
 
     let table = Table::new(rows, widths).header(header).block(block);
 
@@ -399,6 +931,44 @@ fn draw_table(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_stateful_widget(scrollbar, scrollbar_area, &mut app.scrollbar_state);
 }
 
+/// The few keys the footer always shows; the rest are behind `?`
+const FOOTER_KEYS: &[(&str, &str)] = &[
+    ("q", "quit"),
+    ("j/k", "nav"),
+    ("x/X", "kill/tree"),
+    ("s", "sort"),
+    ("a", "all"),
+    ("r", "refresh"),
+    ("?", "help"),
+];
+
+/// Every key in the main view, for the `?` popup
+const HELP_KEYS: &[(&str, &str)] = &[
+    ("q", "quit, or close a popup"),
+    ("j/k ↑/↓", "move the selection"),
+    ("h/l ←/→", "scroll the command line"),
+    ("enter", "expand a grouped row"),
+    ("x", "kill the selected process"),
+    ("X", "kill its whole tree"),
+    ("z/Z", "suspend/resume"),
+    ("s/S", "sort column/reverse"),
+    ("a", "all listeners or dev only"),
+    ("g", "group ports by process"),
+    ("u", "cycle protocol filter"),
+    ("U", "include Unix sockets"),
+    ("t", "process tree"),
+    ("m", "only my processes"),
+    ("c", "show/hide/only containers"),
+    ("i", "I/O columns"),
+    ("f", "file descriptor column"),
+    ("e", "environment"),
+    ("w", "who holds the port"),
+    ("M", "cycle memory figure"),
+    ("d", "memory breakdown pane"),
+    ("C", "CPU% per core or machine"),
+    ("r", "refresh now"),
+];
+
 fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
     let status = if let Some(ref msg) = app.status_message {
         Span::styled(
@@ -424,39 +994,23 @@ fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
     let label_style = Style::default().fg(Color::DarkGray);
     let sep = Span::styled("│", label_style);
 
-    let footer = Line::from(vec![
-        Span::styled(" q", key_style),
-        Span::styled(" quit ", label_style),
-        sep.clone(),
-        Span::styled(" j/k", key_style),
-        Span::styled(" nav ", label_style),
-        sep.clone(),
-        Span::styled(" x", key_style),
-        Span::styled(" kill ", label_style),
-        sep.clone(),
-        Span::styled(" s", key_style),
-        Span::styled(" sort ", label_style),
-        sep.clone(),
-        Span::styled(" S", key_style),
-        Span::styled(" reverse ", label_style),
-        sep.clone(),
-        Span::styled(" a", key_style),
-        Span::styled(" all ", label_style),
-        sep.clone(),
-        Span::styled(" r", key_style),
-        Span::styled(" refresh ", label_style),
-        sep,
-        Span::raw(" "),
-        Span::styled(
-            format!(" {} ", count),
-            Style::default()
-                .fg(Color::Black)
-                .bg(badge_color)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(" processes ", Style::default().fg(Color::DarkGray)),
-        status,
-    ]);
+    // The status goes first so a narrow terminal cuts hints, not messages
+    let mut spans = vec![status];
+    for (key, label) in FOOTER_KEYS {
+        spans.push(Span::styled(format!(" {}", key), key_style));
+        spans.push(Span::styled(format!(" {} ", label), label_style));
+        spans.push(sep.clone());
+    }
+    spans.push(Span::raw(" "));
+    spans.push(Span::styled(
+        format!(" {} ", count),
+        Style::default()
+            .fg(Color::Black)
+            .bg(badge_color)
+            .add_modifier(Modifier::BOLD),
+    ));
+    spans.push(Span::styled(" processes ", Style::default().fg(Color::DarkGray)));
+    let footer = Line::from(spans);
 
     let block = Block::default()
         .borders(Borders::ALL)
@@ -468,24 +1022,34 @@ fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
 
 fn draw_kill_confirm(frame: &mut Frame, app: &App) {
     let area = frame.area();
-    let popup_width = 50u16.min(area.width.saturating_sub(4));
-    let popup_height = 5u16;
-    let x = (area.width.saturating_sub(popup_width)) / 2;
-    let y = (area.height.saturating_sub(popup_height)) / 2;
-    let popup_area = Rect::new(x, y, popup_width, popup_height);
-
-    let text = if let Some((pid, ref name, port)) = app.kill_target {
-        Line::from(vec![
-            Span::raw("Kill "),
-            Span::styled(
-                name.clone(),
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(format!(" (PID {}) on ", pid)),
+    let popup_width = 60u16.min(area.width.saturating_sub(4));
+    let name_style = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let text = if let Some(ref target) = app.kill_target {
+        let mut spans = Vec::new();
+        match target.tree.last() {
+            Some(root) => {
+                spans.push(Span::raw(format!("Kill {} processes under ", target.tree.len())));
+                spans.push(Span::styled(root.name.clone(), name_style));
+                spans.push(Span::raw(format!(" (PID {})", root.pid)));
+                if root.pid != target.pid {
+                    spans.push(Span::raw(", which started "));
+                    spans.push(Span::styled(target.name.clone(), name_style));
+                    spans.push(Span::raw(format!(" (PID {})", target.pid)));
+                }
+            }
+            None => {
+                let verb = if target.signal == Signal::Stop { "Suspend " } else { "Kill " };
+                spans.push(Span::raw(verb));
+                spans.push(Span::styled(target.name.clone(), name_style));
+                spans.push(Span::raw(format!(" (PID {})", target.pid)));
+            }
+        }
+        spans.extend([
+            Span::raw(" on "),
             Span::styled(
-                format!(":{}", port),
+                target.ports.clone(),
                 Style::default()
                     .fg(Color::Green)
                     .add_modifier(Modifier::BOLD),
@@ -497,18 +1061,63 @@ fn draw_kill_confirm(frame: &mut Frame, app: &App) {
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD),
             ),
-        ])
+        ]);
+        Line::from(spans)
     } else {
         Line::from("No process selected")
     };
 
+    let mut text = vec![text];
+    let suspending = app.kill_target.as_ref().is_some_and(|t| t.signal == Signal::Stop);
+    if suspending {
+        text.push(Line::from(Span::styled(
+            "It keeps its port but stops answering until Z resumes it",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    if let Some(target) = app.kill_target.as_ref().filter(|t| !t.tree.is_empty()) {
+        text.push(Line::from(Span::styled(
+            format!("signals {}", tree_summary(&target.tree)),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    if let Some((_, ref name)) = app.kill_target.as_ref().and_then(|t| t.container.as_ref()) {
+        let key_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        text.push(Line::from(vec![
+            Span::raw(format!("container {}: ", name)),
+            Span::styled("[s]", key_style),
+            Span::raw(" stop  "),
+            Span::styled("[r]", key_style),
+            Span::raw(" restart"),
+        ]));
+    }
+    if let Some(owner) = app.kill_target.as_ref().and_then(|t| t.foreign_owner.as_ref()) {
+        text.push(Line::from(Span::styled(
+            format!("\u{26a0} owned by {} \u{2014} you may lack permission", owner),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )));
+    }
+
+    // Long trees wrap; leave a spare row since wrapping breaks at words
+    let inner_width = usize::from(popup_width.saturating_sub(2)).max(1);
+    let rows: usize = text.iter().map(|line| line.width().div_ceil(inner_width).max(1)).sum();
+    let popup_height = (rows as u16 + 3).max(5);
+    let x = (area.width.saturating_sub(popup_width)) / 2;
+    let y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
     let popup = Paragraph::new(text)
         .style(Style::default().fg(Color::White))
         .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
         .block(
             Block::default()
                 .title(Span::styled(
-                    " Confirm Kill ",
+                    if suspending { " Confirm Suspend " } else { " Confirm Kill " },
                     Style::default()
                         .fg(Color::Red)
                         .add_modifier(Modifier::BOLD),
@@ -521,3 +1130,279 @@ fn draw_kill_confirm(frame: &mut Frame, app: &App) {
     frame.render_widget(Clear, popup_area);
     frame.render_widget(popup, popup_area);
 }
+
+/// "esbuild ×2, node ×2", in kill order
+fn tree_summary(tree: &[ProcNode]) -> String {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for node in tree {
+        match counts.iter_mut().find(|(name, _)| *name == node.name) {
+            Some(entry) => entry.1 += 1,
+            None => counts.push((&node.name, 1)),
+        }
+    }
+    counts
+        .iter()
+        .map(|(name, count)| match count {
+            1 => name.to_string(),
+            n => format!("{} \u{d7}{}", name, n),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn draw_env(frame: &mut Frame, view: &EnvView) {
+    let area = frame.area();
+    let popup_width = 110u16.min(area.width.saturating_sub(4));
+    let popup_height = area.height.saturating_sub(4);
+    let x = (area.width.saturating_sub(popup_width)) / 2;
+    let y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    let key_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let label_style = Style::default().fg(Color::DarkGray);
+    let vars = view.visible();
+
+    let search = if view.searching || !view.query.is_empty() {
+        let cursor = if view.searching { "\u{2581}" } else { "" };
+        Line::from(vec![
+            Span::styled(" / ", key_style),
+            Span::styled(format!("{}{}", view.query, cursor), Style::default().fg(Color::White)),
+            Span::styled(format!("  {} of {}", vars.len(), view.total()), label_style),
+        ])
+    } else {
+        Line::from(vec![
+            Span::styled(" /", key_style),
+            Span::styled(" search ", label_style),
+            Span::styled(" v", key_style),
+            Span::styled(if view.reveal { " hide secrets " } else { " reveal secrets " }, label_style),
+            Span::styled(" esc", key_style),
+            Span::styled(" close", label_style),
+        ])
+    };
+
+    let mut lines = vec![search, Line::from("")];
+    if vars.is_empty() {
+        lines.push(Line::from(Span::styled(
+            if view.total() == 0 { " (empty environment)" } else { " no matches" },
+            label_style,
+        )));
+    }
+    let rows = popup_height.saturating_sub(4) as usize;
+    for (key, value, masked) in vars.into_iter().skip(view.scroll).take(rows) {
+        let key_span = if environ::is_interesting(key) {
+            Span::styled(format!(" {}", key), key_style)
+        } else {
+            Span::styled(format!(" {}", key), Style::default().fg(Color::Cyan))
+        };
+        let value_style = if masked {
+            Style::default().fg(Color::DarkGray)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        lines.push(Line::from(vec![
+            key_span,
+            Span::styled("=", label_style),
+            Span::styled(value, value_style),
+        ]));
+    }
+
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .title(Span::styled(
+                format!(" Environment \u{00b7} {} (PID {}) ", view.name, view.pid),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .borders(Borders::ALL)
+            .border_set(BORDER_SET)
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(popup, popup_area);
+}
+
+fn draw_port_report(frame: &mut Frame, report: &PortReport) {
+    let area = frame.area();
+    let label_style = Style::default().fg(Color::DarkGray);
+    let popup_width = 110u16.min(area.width.saturating_sub(4));
+    // Reasons wrap, so leave them a couple of lines each
+    let wanted = 5 + report.sockets.len().max(1) + report.reasons.len() * 2;
+    let popup_height = (wanted as u16).min(area.height.saturating_sub(4));
+    let x = (area.width.saturating_sub(popup_width)) / 2;
+    let y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    let mut lines = Vec::new();
+    if report.sockets.is_empty() {
+        lines.push(Line::from(Span::styled(" No TCP or UDP sockets on this port", label_style)));
+    } else {
+        lines.push(Line::from(Span::styled(
+            format!(" {:<5} {:<12} {:<28} {:<28} OWNER", "PROTO", "STATE", "LOCAL", "REMOTE"),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )));
+    }
+    // Leave room for the explanation below the table
+    let room = (popup_height as usize).saturating_sub(4 + report.reasons.len() * 2);
+    let shown = if report.sockets.len() > room { room.saturating_sub(1) } else { room };
+    for s in report.sockets.iter().take(shown) {
+        let state_style = match s.state {
+            TcpState::Listen => Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+            TcpState::Established => Style::default().fg(Color::Cyan),
+            // A bound UDP socket, UDP's nearest thing to a listener
+            TcpState::Close if s.is_udp() => Style::default().fg(Color::Green),
+            TcpState::TimeWait => label_style,
+            _ => Style::default().fg(Color::Yellow),
+        };
+        let owner = match s.owner {
+            Some((pid, ref name)) => Span::styled(format!("{} ({})", name, pid), Style::default().fg(Color::White)),
+            None => Span::styled("-", label_style),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {:<5} ", s.protocol), label_style),
+            Span::styled(format!("{:<12} ", s.state_label()), state_style),
+            Span::raw(format!("{:<28} {:<28} ", s.local, s.remote)),
+            owner,
+        ]));
+    }
+    if report.sockets.len() > shown {
+        lines.push(Line::from(Span::styled(
+            format!(" \u{2026} {} more", report.sockets.len() - shown),
+            label_style,
+        )));
+    }
+    lines.push(Line::from(""));
+    for reason in &report.reasons {
+        lines.push(Line::from(vec![
+            Span::styled(" \u{2022} ", Style::default().fg(Color::Yellow)),
+            Span::raw(reason.clone()),
+        ]));
+    }
+
+    let popup = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .title(Span::styled(
+                    format!(" Who holds :{} ", report.port),
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ))
+                .title_bottom(Span::styled(" esc close ", label_style))
+                .borders(Borders::ALL)
+                .border_set(BORDER_SET)
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(popup, popup_area);
+}
+
+fn draw_help(frame: &mut Frame) {
+    let area = frame.area();
+    let popup_width = 46u16.min(area.width.saturating_sub(4));
+    let popup_height = (HELP_KEYS.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let x = (area.width.saturating_sub(popup_width)) / 2;
+    let y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    let key_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let lines: Vec<Line> = HELP_KEYS
+        .iter()
+        .map(|(key, what)| {
+            Line::from(vec![
+                Span::styled(format!(" {:<9}", key), key_style),
+                Span::raw(*what),
+            ])
+        })
+        .collect();
+
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .title(Span::styled(
+                " Keys ",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .title_bottom(Span::styled(" esc close ", Style::default().fg(Color::DarkGray)))
+            .borders(Borders::ALL)
+            .border_set(BORDER_SET)
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(popup, popup_area);
+}
+
+fn draw_memory_detail(frame: &mut Frame, app: &App, area: Rect) {
+    let label_style = Style::default().fg(Color::DarkGray);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(BORDER_SET)
+        .border_style(Style::default().fg(Color::DarkGray))
+        .title(Span::styled(
+            " Memory ",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+
+    let Some(p) = app.selected_process() else {
+        frame.render_widget(Paragraph::new(Span::styled(" No process selected", label_style)).block(block), area);
+        return;
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(format!(" {}", p.name), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::styled(format!(" PID {}", p.pid), label_style),
+        ]),
+        Line::from(""),
+    ];
+    let metrics = [
+        (MemoryMetric::Rss, "RSS"),
+        (MemoryMetric::Pss, "PSS"),
+        (MemoryMetric::Uss, "USS"),
+        (MemoryMetric::Swap, "Swap"),
+        (MemoryMetric::Virtual, "Virtual"),
+    ];
+    for (metric, label) in metrics {
+        let label_span = if metric == app.memory_metric {
+            Span::styled(
+                format!(" \u{25b8} {:<8}", label),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Span::styled(format!("   {:<8}", label), Style::default().fg(Color::Gray))
+        };
+        let value = metric.of(p).map_or("-".to_string(), scanner::format_bytes);
+        lines.push(Line::from(vec![
+            label_span,
+            Span::styled(format!("{:>10}", value), Style::default().fg(Color::White)),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    let notes = if p.memory_detail.pss.is_none() {
+        " PSS and USS need ptrace access to the process (same user or root)."
+    } else {
+        " RSS counts shared pages in full for every process. PSS splits them between the sharers, \
+         so it adds up across a forked worker pool; USS is what exiting would free."
+    };
+    lines.push(Line::from(Span::styled(notes, label_style)));
+
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(block), area);
+}
@@ -1,35 +1,59 @@
-use crate::app::{App, Message, OwlMood, SortColumn, SortDirection};
+use crate::app::{App, ContainerFilter, CpuMode, KillTarget, Message, OwlMood, ProtoFilter, SortColumn, SortDirection};
+use crate::docker;
+use crate::environ::EnvView;
+use crate::inspect::PortReport;
+use crate::source::Signal;
+use crate::tree::ProcNode;
 
-pub fn update(app: &mut App, msg: Message) {
-    // Tick down status message timer
-    if app.status_timer > 0 {
-        app.status_timer -= 1;
-        if app.status_timer == 0 {
-            app.status_message = None;
-        }
-    }
+const CMD_SCROLL_STEP: usize = 8;
 
+/// Whether `pid` still runs as `name`, so a PID reused since the kill dialog
+/// read it isn't signalled
+fn still_running(app: &App, pid: u32, name: &str) -> bool {
+    app.worker.source().process_name(pid).as_deref() == Some(name)
+}
+
+pub fn update(app: &mut App, msg: Message) {
     match msg {
         Message::Quit => {
             if app.show_kill_confirm {
                 app.show_kill_confirm = false;
+            } else if app.show_help {
+                app.show_help = false;
+            } else if app.port_report.is_some() {
+                app.port_report = None;
+            } else if app.env_view.is_some() {
+                app.env_view = None;
             } else {
                 app.running = false;
             }
         }
         Message::Tick => {
+            // Status messages last a few ticks, however many keys and scans
+            // arrive meanwhile
+            if app.status_timer > 0 {
+                app.status_timer -= 1;
+                if app.status_timer == 0 {
+                    app.status_message = None;
+                }
+            }
             app.refresh();
         }
+        Message::Scanned(output) => {
+            app.apply_scan(*output);
+        }
         Message::Refresh => {
-            app.refresh();
-            app.status_message = Some("Refreshed".to_string());
-            app.status_timer = 3;
+            app.announce_refresh = Some(app.refresh());
             app.set_owl_mood(OwlMood::Flap, 800);
         }
         Message::NavigateUp => {
             if app.show_kill_confirm {
                 return;
             }
+            if let Some(ref mut view) = app.env_view {
+                view.scroll_up();
+                return;
+            }
             if app.selected > 0 {
                 app.selected -= 1;
             } else if !app.processes.is_empty() {
@@ -42,6 +66,10 @@ pub fn update(app: &mut App, msg: Message) {
             if app.show_kill_confirm {
                 return;
             }
+            if let Some(ref mut view) = app.env_view {
+                view.scroll_down();
+                return;
+            }
             if app.selected + 1 < app.processes.len() {
                 app.selected += 1;
             } else {
@@ -50,25 +78,107 @@ pub fn update(app: &mut App, msg: Message) {
             app.scrollbar_state = app.scrollbar_state.position(app.selected);
             app.set_owl_mood(OwlMood::LookDown, 500);
         }
-        Message::Kill => {
+        Message::Kill | Message::KillTree => {
             if !app.show_kill_confirm {
+                let whole_tree = matches!(msg, Message::KillTree);
                 if let Some(p) = app.selected_process() {
-                    app.kill_target = Some((p.pid, p.name.clone(), p.port));
-                    app.show_kill_confirm = true;
-                    app.set_owl_mood(OwlMood::Alarmed, 2000);
+                    let foreign_owner = app.foreign_owner(p);
+                    let container = p.container.as_ref().and_then(|c| match (&c.id, &c.name) {
+                        (Some(id), Some(name)) => Some((id.clone(), name.clone())),
+                        _ => None,
+                    });
+                    let pid = p.pid;
+                    app.kill_target = Some(KillTarget {
+                        pid,
+                        name: p.name.clone(),
+                        ports: p.port_label(),
+                        tree: Vec::new(),
+                        foreign_owner,
+                        container,
+                        signal: Signal::Term,
+                    });
+                    if whole_tree {
+                        // Reading the whole process table is slow; the dialog
+                        // opens when `KillOrder` comes back
+                        app.worker.run(Box::new(move |source| {
+                            Message::KillOrder(pid, source.kill_order(pid))
+                        }));
+                    } else {
+                        app.show_kill_confirm = true;
+                        app.set_owl_mood(OwlMood::Alarmed, 2000);
+                    }
                 }
             }
         }
+        Message::KillOrder(pid, tree) => {
+            // Ignored if another kill dialog opened while the tree was read
+            let Some(target) = app.kill_target.as_mut().filter(|t| t.pid == pid) else {
+                return;
+            };
+            if !app.show_kill_confirm {
+                target.tree = tree;
+                app.show_kill_confirm = true;
+                app.set_owl_mood(OwlMood::Alarmed, 2000);
+            }
+        }
         Message::ConfirmKill => {
             if app.show_kill_confirm {
-                if let Some((pid, name, _port)) = app.kill_target.take() {
-                    match kill_process(pid) {
-                        Ok(()) => {
-                            app.status_message = Some(format!("Killed {} (PID {})", name, pid));
+                if let Some(target) = app.kill_target.take() {
+                    let (pid, name) = (target.pid, target.name);
+                    if target.tree.is_empty() {
+                        let (done, verb) = match target.signal {
+                            Signal::Stop => ("Suspended", "suspend"),
+                            _ => ("Killed", "kill"),
+                        };
+                        // The PID may have exited, or been reused, since the dialog opened
+                        if !still_running(app, pid, &name) {
+                            app.status_message =
+                                Some(format!("PID {} is no longer {}; nothing to {}", pid, name, verb));
+                        } else {
+                            match app.worker.source().signal(pid, target.signal) {
+                                Ok(()) => {
+                                    app.status_message = Some(format!("{} {} (PID {})", done, name, pid));
+                                }
+                                Err(e) => {
+                                    app.status_message = Some(match target.foreign_owner {
+                                        Some(owner) => format!(
+                                            "Can't {} PID {}: owned by {} ({})",
+                                            verb, pid, owner, e
+                                        ),
+                                        None => format!("Failed to {} PID {}: {}", verb, pid, e),
+                                    });
+                                }
+                            }
+                        }
+                    } else {
+                        let name = target.tree.last().map_or(name, |root| root.name.clone());
+                        let (running, gone): (Vec<&ProcNode>, Vec<&ProcNode>) = target
+                            .tree
+                            .iter()
+                            .partition(|node| still_running(app, node.pid, &node.name));
+                        let failed: Vec<u32> = running
+                            .iter()
+                            .map(|node| node.pid)
+                            .filter(|pid| app.worker.source().signal(*pid, Signal::Term).is_err())
+                            .collect();
+                        let mut status = if failed.is_empty() && gone.is_empty() {
+                            format!("Killed {} processes in {}'s tree", target.tree.len(), name)
+                        } else {
+                            format!(
+                                "Killed {}/{} in {}'s tree",
+                                running.len() - failed.len(),
+                                target.tree.len(),
+                                name
+                            )
+                        };
+                        if !failed.is_empty() {
+                            status.push_str(&format!(" (failed: {:?})", failed));
                         }
-                        Err(e) => {
-                            app.status_message = Some(format!("Failed to kill PID {}: {}", pid, e));
+                        if !gone.is_empty() {
+                            let pids: Vec<u32> = gone.iter().map(|node| node.pid).collect();
+                            status.push_str(&format!(" (already gone: {:?})", pids));
                         }
+                        app.status_message = Some(status);
                     }
                     app.status_timer = 3;
                     app.set_owl_mood(OwlMood::Alarmed, 1500);
@@ -77,6 +187,39 @@ pub fn update(app: &mut App, msg: Message) {
                 app.refresh();
             }
         }
+        Message::StopContainer | Message::RestartContainer => {
+            let stop = matches!(msg, Message::StopContainer);
+            let Some((id, name)) = app.kill_target.as_ref().and_then(|t| t.container.clone()) else {
+                return;
+            };
+            // `docker stop` blocks for the container's grace period
+            app.status_message = Some(format!(
+                "{} container {}\u{2026}",
+                if stop { "Stopping" } else { "Restarting" },
+                name
+            ));
+            app.status_timer = 3;
+            app.worker.run(Box::new(move |_| {
+                let client = docker::Client::from_env();
+                let (verb, result) = if stop {
+                    ("Stopped", client.stop(&id))
+                } else {
+                    ("Restarted", client.restart(&id))
+                };
+                Message::ContainerDone(match result {
+                    Ok(()) => format!("{} container {}", verb, name),
+                    Err(e) => format!("Docker: {}", e),
+                })
+            }));
+            app.set_owl_mood(OwlMood::Alarmed, 1500);
+            app.kill_target = None;
+            app.show_kill_confirm = false;
+        }
+        Message::ContainerDone(status) => {
+            app.status_message = Some(status);
+            app.status_timer = 3;
+            app.refresh();
+        }
         Message::CancelKill => {
             app.show_kill_confirm = false;
             app.kill_target = None;
@@ -87,6 +230,7 @@ pub fn update(app: &mut App, msg: Message) {
             }
             app.show_all = !app.show_all;
             app.selected = 0;
+            app.rebuild();
             app.refresh();
             app.set_owl_mood(OwlMood::WideEye, 800);
         }
@@ -96,15 +240,17 @@ pub fn update(app: &mut App, msg: Message) {
             }
             app.sort_column = match app.sort_column {
                 SortColumn::Pid => SortColumn::Name,
-                SortColumn::Name => SortColumn::Port,
+                SortColumn::Name => SortColumn::Project,
+                SortColumn::Project => SortColumn::Port,
                 SortColumn::Port => SortColumn::Proto,
-                SortColumn::Proto => SortColumn::Cpu,
+                SortColumn::Proto => SortColumn::Conn,
+                SortColumn::Conn => SortColumn::Cpu,
                 SortColumn::Cpu => SortColumn::Memory,
                 SortColumn::Memory => SortColumn::Uptime,
                 SortColumn::Uptime => SortColumn::Pid,
             };
             app.sort_direction = SortDirection::Ascending;
-            app.refresh();
+            app.rebuild();
         }
         Message::ToggleSortDirection => {
             if app.show_kill_confirm {
@@ -114,37 +260,552 @@ pub fn update(app: &mut App, msg: Message) {
                 SortDirection::Ascending => SortDirection::Descending,
                 SortDirection::Descending => SortDirection::Ascending,
             };
+            app.rebuild();
+        }
+        Message::ToggleGroup => {
+            if app.show_kill_confirm {
+                return;
+            }
+            app.grouped = !app.grouped;
+            app.expanded.clear();
+            app.selected = 0;
+            app.rebuild();
+            app.status_message = Some(if app.grouped {
+                "Grouped by process".to_string()
+            } else {
+                "One row per listener".to_string()
+            });
+            app.status_timer = 3;
+        }
+        Message::CycleProto => {
+            if app.show_kill_confirm {
+                return;
+            }
+            app.filter_proto = match app.filter_proto {
+                None => Some(ProtoFilter::Tcp),
+                Some(ProtoFilter::Tcp) => Some(ProtoFilter::Udp),
+                Some(ProtoFilter::Udp) if app.show_unix => Some(ProtoFilter::Unix),
+                Some(ProtoFilter::Udp) | Some(ProtoFilter::Unix) => None,
+            };
+            app.selected = 0;
+            app.rebuild();
+            app.set_owl_mood(OwlMood::WideEye, 800);
+        }
+        Message::ScrollLeft => {
+            app.cmd_scroll = app.cmd_scroll.saturating_sub(CMD_SCROLL_STEP);
+        }
+        Message::ScrollRight => {
+            let longest = app
+                .processes
+                .iter()
+                .map(|p| p.cmdline.chars().count())
+                .max()
+                .unwrap_or(0);
+            if app.cmd_scroll + CMD_SCROLL_STEP < longest {
+                app.cmd_scroll += CMD_SCROLL_STEP;
+            }
+        }
+        Message::ToggleTree => {
+            if app.show_kill_confirm {
+                return;
+            }
+            app.tree_view = !app.tree_view;
+            app.selected = 0;
+            app.rebuild();
+            app.refresh();
+            app.set_owl_mood(OwlMood::WideEye, 800);
+        }
+        Message::ToggleMine => {
+            if app.show_kill_confirm {
+                return;
+            }
+            app.mine_only = !app.mine_only;
+            app.selected = 0;
+            app.rebuild();
+            app.set_owl_mood(OwlMood::WideEye, 800);
+        }
+        Message::CycleContainers => {
+            if app.show_kill_confirm {
+                return;
+            }
+            app.container_filter = match app.container_filter {
+                ContainerFilter::Show => ContainerFilter::Hide,
+                ContainerFilter::Hide => ContainerFilter::Only,
+                ContainerFilter::Only => ContainerFilter::Show,
+            };
+            app.selected = 0;
+            app.rebuild();
+            app.set_owl_mood(OwlMood::WideEye, 800);
+        }
+        Message::ToggleIo => {
+            app.show_io = !app.show_io;
+        }
+        Message::ToggleFds => {
+            app.show_fds = !app.show_fds;
+        }
+        Message::ToggleMemoryDetail => {
+            let had_detail = app.needs_memory_detail();
+            app.show_memory_detail = !app.show_memory_detail;
+            if !had_detail {
+                app.refresh();
+            }
+        }
+        Message::ToggleCpuMode => {
+            app.cpu_mode = match app.cpu_mode {
+                CpuMode::PerCore => CpuMode::Machine,
+                CpuMode::Machine => CpuMode::PerCore,
+            };
+            app.status_message = Some(match app.cpu_mode {
+                CpuMode::PerCore => "CPU% per core".to_string(),
+                CpuMode::Machine => format!("CPU% of all {} cores", app.cores()),
+            });
+            app.status_timer = 3;
+        }
+        Message::CycleMemoryMetric => {
+            if app.show_kill_confirm {
+                return;
+            }
+            let had_detail = app.needs_memory_detail();
+            app.memory_metric = app.memory_metric.next();
+            // Scans skip the breakdown while only RSS is shown
+            if !had_detail {
+                app.refresh();
+            }
+            app.rebuild();
+            app.status_message = Some(format!("MEMORY shows {}", app.memory_metric.column()));
+            app.status_timer = 3;
+        }
+        Message::ToggleUnix => {
+            if app.show_kill_confirm {
+                return;
+            }
+            app.show_unix = !app.show_unix;
+            if !app.show_unix && app.filter_proto == Some(ProtoFilter::Unix) {
+                app.filter_proto = None;
+            }
+            app.selected = 0;
+            app.rebuild();
             app.refresh();
+            app.set_owl_mood(OwlMood::WideEye, 800);
+        }
+        Message::Suspend | Message::Resume => {
+            if app.show_kill_confirm {
+                return;
+            }
+            let Some(p) = app.selected_process() else {
+                return;
+            };
+            let suspend = matches!(msg, Message::Suspend);
+            let verb = if suspend { "suspend" } else { "resume" };
+            // SIGSTOP to init or someone else's process is never what was meant
+            let refusal = if p.pid <= 1 {
+                Some(format!("Can't {} PID {} ({}): that would stop the system", verb, p.pid, p.name))
+            } else {
+                app.foreign_owner(p)
+                    .map(|owner| format!("Can't {} PID {}: owned by {}", verb, p.pid, owner))
+            };
+            if let Some(refusal) = refusal {
+                app.status_message = Some(refusal);
+                app.status_timer = 3;
+                return;
+            }
+
+            let (pid, name) = (p.pid, p.name.clone());
+            if suspend {
+                app.kill_target = Some(KillTarget {
+                    pid,
+                    name,
+                    ports: p.port_label(),
+                    tree: Vec::new(),
+                    foreign_owner: None,
+                    container: None,
+                    signal: Signal::Stop,
+                });
+                app.show_kill_confirm = true;
+                return;
+            }
+            app.status_message = Some(match app.worker.source().signal(pid, Signal::Cont) {
+                Ok(()) => format!("Resumed {} (PID {})", name, pid),
+                Err(e) => format!("Can't resume PID {}: {}", pid, e),
+            });
+            app.status_timer = 3;
+            app.refresh();
+        }
+        Message::InspectPort => {
+            if app.show_kill_confirm {
+                return;
+            }
+            // With nothing listening, `-p 3000` still says which port to look at
+            let port = match app.selected_process() {
+                Some(p) if p.is_unix() => None,
+                Some(p) => Some(p.port),
+                None => app.filter_port,
+            };
+            let Some(port) = port else {
+                app.status_message = Some("Select a TCP or UDP row to inspect its port".to_string());
+                app.status_timer = 3;
+                return;
+            };
+            // Finding socket owners walks every process's fd table
+            app.worker.run(Box::new(move |source| {
+                let report = source.port_sockets(port).map(|sockets| PortReport::new(port, sockets));
+                Message::PortInspected(port, report)
+            }));
+        }
+        Message::PortInspected(port, report) => match report {
+            Ok(report) => app.port_report = Some(report),
+            Err(e) => {
+                app.status_message = Some(format!("Can't inspect :{}: {}", port, e));
+                app.status_timer = 3;
+            }
+        },
+        Message::CloseInspect => {
+            app.port_report = None;
+        }
+        Message::ToggleHelp => {
+            if app.show_kill_confirm {
+                return;
+            }
+            app.show_help = !app.show_help;
+        }
+        Message::ShowEnv => {
+            if app.show_kill_confirm {
+                return;
+            }
+            let Some((pid, name)) = app.selected_process().map(|p| (p.pid, p.name.clone())) else {
+                return;
+            };
+            match app.worker.source().environ(pid) {
+                Ok(vars) => app.env_view = Some(EnvView::new(pid, &name, vars)),
+                Err(e) => {
+                    app.status_message = Some(format!("Can't read environment of PID {}: {}", pid, e));
+                    app.status_timer = 3;
+                }
+            }
+        }
+        Message::CloseEnv => {
+            app.env_view = None;
+        }
+        Message::StartEnvSearch => {
+            if let Some(ref mut view) = app.env_view {
+                view.searching = true;
+            }
+        }
+        Message::EnvSearchInput(c) => {
+            if let Some(ref mut view) = app.env_view {
+                let query = format!("{}{}", view.query, c);
+                view.set_query(query);
+            }
+        }
+        Message::EnvSearchBackspace => {
+            if let Some(ref mut view) = app.env_view {
+                let mut query = view.query.clone();
+                query.pop();
+                view.set_query(query);
+            }
+        }
+        Message::EndEnvSearch => {
+            if let Some(ref mut view) = app.env_view {
+                view.searching = false;
+            }
+        }
+        Message::CancelEnvSearch => {
+            if let Some(ref mut view) = app.env_view {
+                view.searching = false;
+                view.set_query(String::new());
+            }
+        }
+        Message::RevealSecrets => {
+            if let Some(ref mut view) = app.env_view {
+                view.reveal = !view.reveal;
+                view.scroll = 0;
+            }
+        }
+        Message::ToggleExpand => {
+            if app.show_kill_confirm || !app.grouped {
+                return;
+            }
+            if let Some(pid) = app.selected_process().map(|p| p.pid) {
+                if !app.expanded.remove(&pid) {
+                    app.expanded.insert(pid);
+                }
+            }
         }
     }
 }
 
-#[cfg(unix)]
-fn kill_process(pid: u32) -> Result<(), String> {
-    use std::process::Command;
-    let output = Command::new("kill")
-        .args(["-TERM", &pid.to_string()])
-        .output()
-        .map_err(|e| e.to_string())?;
-    if output.status.success() {
-        Ok(())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(stderr.trim().to_string())
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::{self, Receiver};
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::app::{InputMode, MemoryMetric, Options};
+    use crate::scanner::{DevProcess, ProcState};
+    use crate::source::{FakeSource, ProcessSource};
+    use crate::worker::Worker;
+
+    fn process(pid: u32, name: &str, port: u16) -> DevProcess {
+        DevProcess {
+            pid,
+            ..DevProcess::test(name, port)
+        }
+    }
+
+    fn app_with(processes: Vec<DevProcess>) -> (App, Receiver<Message>, Arc<FakeSource>) {
+        app_over(FakeSource::new(processes))
+    }
+
+    fn app_over(source: FakeSource) -> (App, Receiver<Message>, Arc<FakeSource>) {
+        let source = Arc::new(source);
+        let (tx, rx) = mpsc::channel();
+        let options = Options {
+            tick_rate_secs: 2,
+            ..Options::default()
+        };
+        let mut app = App::new(options, Worker::inline(source.clone(), tx));
+        pump(&mut app, &rx);
+        (app, rx, source)
+    }
+
+    /// Feeds posted messages back in, as the event loop does
+    fn pump(app: &mut App, rx: &Receiver<Message>) {
+        while let Ok(msg) = rx.try_recv() {
+            update(app, msg);
+        }
+    }
+
+    #[test]
+    fn first_scan_fills_dev_rows() {
+        let (mut app, rx, _) = app_with(vec![process(10, "node", 3000), process(11, "sshd", 22)]);
+        assert!(!app.worker.busy());
+        assert_eq!(app.processes.len(), 1);
+
+        update(&mut app, Message::ToggleAll);
+        assert_eq!(app.processes.len(), 2);
+        pump(&mut app, &rx);
+        assert_eq!(app.processes.len(), 2);
+    }
+
+    #[test]
+    fn confirmed_kill_signals_and_rescans() {
+        let (mut app, rx, source) = app_with(vec![process(10, "node", 3000), process(12, "vite", 5173)]);
+        update(&mut app, Message::Kill);
+        assert!(app.show_kill_confirm);
+
+        update(&mut app, Message::ConfirmKill);
+        pump(&mut app, &rx);
+        assert_eq!(source.killed(), vec![10]);
+        assert_eq!(app.status_message.as_deref(), Some("Killed node (PID 10)"));
+        assert_eq!(app.processes.len(), 1);
+        assert_eq!(app.processes[0].pid, 12);
+    }
+
+    #[test]
+    fn kill_tree_opens_once_the_tree_is_read() {
+        let (mut app, rx, source) = app_with(vec![process(10, "node", 3000)]);
+        update(&mut app, Message::KillTree);
+        assert!(!app.show_kill_confirm);
+        pump(&mut app, &rx);
+        assert!(app.show_kill_confirm);
+        assert_eq!(app.kill_target.as_ref().unwrap().tree[0].name, "node");
+
+        update(&mut app, Message::ConfirmKill);
+        assert_eq!(source.killed(), vec![10]);
+    }
+
+    #[test]
+    fn refreshed_waits_for_the_scan() {
+        let (mut app, rx, _) = app_with(vec![process(10, "node", 3000)]);
+        update(&mut app, Message::Refresh);
+        assert_eq!(app.status_message, None);
+        pump(&mut app, &rx);
+        assert_eq!(app.status_message.as_deref(), Some("Refreshed"));
+
+        update(&mut app, Message::NavigateDown);
+        assert!(app.status_message.is_some());
+        for _ in 0..3 {
+            update(&mut app, Message::Tick);
+            pump(&mut app, &rx);
+        }
+        assert_eq!(app.status_message, None);
+    }
+
+    #[test]
+    fn cancelled_kill_leaves_process() {
+        let (mut app, rx, source) = app_with(vec![process(10, "node", 3000)]);
+        update(&mut app, Message::Kill);
+        update(&mut app, Message::CancelKill);
+        update(&mut app, Message::Tick);
+        pump(&mut app, &rx);
+        assert!(source.killed().is_empty());
+        assert_eq!(app.processes.len(), 1);
+    }
+
+    #[test]
+    fn suspend_and_resume_update_state() {
+        let (mut app, rx, _) = app_with(vec![process(10, "node", 3000)]);
+        update(&mut app, Message::Suspend);
+        assert!(app.show_kill_confirm);
+        assert_ne!(app.processes[0].state, ProcState::Stopped);
+        update(&mut app, Message::ConfirmKill);
+        pump(&mut app, &rx);
+        assert_eq!(app.processes[0].state, ProcState::Stopped);
+        assert_eq!(app.status_message.as_deref(), Some("Suspended node (PID 10)"));
+
+        update(&mut app, Message::Resume);
+        pump(&mut app, &rx);
+        assert_eq!(app.processes[0].state, ProcState::Sleeping);
+    }
+
+    #[test]
+    fn suspend_refuses_init() {
+        let (mut app, _, _) = app_with(vec![process(1, "systemd", 3000)]);
+        update(&mut app, Message::Suspend);
+        assert!(!app.show_kill_confirm);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Can't suspend PID 1 (systemd): that would stop the system")
+        );
+        update(&mut app, Message::Resume);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Can't resume PID 1 (systemd): that would stop the system")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn suspend_refuses_other_users() {
+        let theirs = DevProcess {
+            uid: sysinfo::Uid::try_from(1001usize).ok(),
+            user: Some("alice".to_string()),
+            ..process(11, "postgres", 5432)
+        };
+        let (mut app, _, _) = app_with(vec![theirs]);
+        app.current_uid = sysinfo::Uid::try_from(1000usize).ok();
+
+        update(&mut app, Message::Resume);
+        assert!(!app.show_kill_confirm);
+        assert_eq!(app.status_message.as_deref(), Some("Can't resume PID 11: owned by alice"));
+    }
+
+    #[test]
+    fn inspect_falls_back_to_port_filter() {
+        let (mut app, rx, _) = app_with(vec![process(10, "node", 3000)]);
+        update(&mut app, Message::InspectPort);
+        assert!(app.port_report.is_none());
+        pump(&mut app, &rx);
+        let report = app.port_report.take().unwrap();
+        assert_eq!(report.port, 3000);
+        assert_eq!(report.sockets.len(), 1);
+
+        app.filter_port = Some(8080);
+        app.rebuild();
+        pump(&mut app, &rx);
+        update(&mut app, Message::InspectPort);
+        pump(&mut app, &rx);
+        assert_eq!(app.input_mode(), InputMode::Inspect);
+        let report = app.port_report.as_ref().unwrap();
+        assert_eq!(report.port, 8080);
+        assert!(report.sockets.is_empty());
+    }
+
+    #[test]
+    fn memory_metric_drives_sort() {
+        let mut small_rss = process(10, "gunicorn", 8000);
+        small_rss.memory_bytes = 100;
+        small_rss.memory_detail.pss = Some(90);
+        let mut shared = process(11, "gunicorn", 8001);
+        shared.memory_bytes = 400;
+        shared.memory_detail.pss = Some(50);
+        let (mut app, _, _) = app_with(vec![small_rss, shared]);
+        app.sort_column = SortColumn::Memory;
+        app.sort_direction = SortDirection::Descending;
+        app.rebuild();
+        assert_eq!(app.processes[0].pid, 11);
+
+        update(&mut app, Message::CycleMemoryMetric);
+        assert_eq!(app.memory_metric, MemoryMetric::Pss);
+        assert_eq!(app.processes[0].pid, 10);
+    }
+
+    #[test]
+    fn memory_breakdown_is_scanned_only_when_shown() {
+        let (mut app, rx, _) = app_with(vec![process(10, "node", 3000)]);
+        assert!(!app.needs_memory_detail());
+
+        update(&mut app, Message::CycleMemoryMetric);
+        assert!(app.needs_memory_detail());
+        let scanned = rx.try_recv().unwrap();
+        assert!(matches!(scanned, Message::Scanned(_)));
+        update(&mut app, scanned);
+        update(&mut app, Message::ToggleMemoryDetail);
+        assert!(rx.try_recv().is_err());
+
+        app.memory_metric = MemoryMetric::Rss;
+        app.show_memory_detail = false;
+        update(&mut app, Message::ToggleMemoryDetail);
+        assert!(matches!(rx.try_recv(), Ok(Message::Scanned(_))));
+    }
+
+    #[test]
+    fn help_popup_closes_before_quitting() {
+        let (mut app, _, _) = app_with(vec![process(10, "node", 3000)]);
+        update(&mut app, Message::ToggleHelp);
+        assert_eq!(app.input_mode(), InputMode::Help);
+        update(&mut app, Message::Quit);
+        assert_eq!(app.input_mode(), InputMode::Normal);
+        assert!(app.running);
+    }
+
+    #[test]
+    fn env_pane_shows_selected_process() {
+        let source = FakeSource::new(vec![process(10, "node", 3000)])
+            .with_env(10, vec![("NODE_ENV".to_string(), "development".to_string())]);
+        let (mut app, _, _) = app_over(source);
+
+        update(&mut app, Message::ShowEnv);
+        assert_eq!(app.input_mode(), InputMode::Env);
+        update(&mut app, Message::StartEnvSearch);
+        for c in "prod".chars() {
+            update(&mut app, Message::EnvSearchInput(c));
+        }
+        assert_eq!(app.input_mode(), InputMode::EnvSearch);
+        assert!(app.env_view.as_ref().unwrap().visible().is_empty());
+
+        update(&mut app, Message::CancelEnvSearch);
+        assert_eq!(app.env_view.as_ref().unwrap().visible().len(), 1);
+        update(&mut app, Message::Quit);
+        assert!(app.env_view.is_none());
+        assert!(app.running);
+    }
+
+    #[test]
+    fn failed_kill_reports_error() {
+        let (mut app, rx, source) = app_with(vec![process(10, "node", 3000)]);
+        update(&mut app, Message::Kill);
+        source.signal(10, Signal::Term).unwrap();
+        update(&mut app, Message::ConfirmKill);
+        pump(&mut app, &rx);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("PID 10 is no longer node; nothing to kill")
+        );
+        assert!(app.processes.is_empty());
     }
-}
 
-#[cfg(windows)]
-fn kill_process(pid: u32) -> Result<(), String> {
-    use std::process::Command;
-    let output = Command::new("taskkill")
-        .args(["/PID", &pid.to_string(), "/F"])
-        .output()
-        .map_err(|e| e.to_string())?;
-    if output.status.success() {
-        Ok(())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(stderr.trim().to_string())
+    #[test]
+    fn reused_pid_is_not_signalled() {
+        let (mut app, rx, source) = app_with(vec![process(10, "node", 3000)]);
+        update(&mut app, Message::KillTree);
+        pump(&mut app, &rx);
+        source.set_processes(vec![process(10, "postgres", 5432)]);
+        update(&mut app, Message::ConfirmKill);
+        assert!(source.killed().is_empty());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Killed 0/1 in node's tree (already gone: [10])")
+        );
     }
 }
@@ -0,0 +1,226 @@
+//! Scanning runs on its own thread so sockets, sysinfo and HTTP probes never
+//! stall key handling or the owl animation.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::app::Message;
+use crate::scanner::{DevProcess, MachineCpu};
+use crate::source::ProcessSource;
+
+/// What the UI wants from the next scan
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScanRequest {
+    /// Read the machine-wide process table for ancestors and descendants
+    pub tree: bool,
+    pub probe_http: bool,
+    /// Probe every listener instead of only dev-relevant ones
+    pub show_all: bool,
+    /// Include Unix domain socket listeners
+    pub unix: bool,
+    /// Read PSS, USS, swap and virtual size, which cost a `smaps_rollup` read
+    /// per listener
+    pub memory_detail: bool,
+}
+
+pub struct ScanOutput {
+    /// Number of the newest request this scan answers, see `Worker::request`
+    pub generation: u64,
+    pub processes: Result<Vec<DevProcess>, String>,
+    pub cost: Duration,
+    /// Processes the source tracks after the scan
+    pub tracked: usize,
+    pub machine_cpu: Option<MachineCpu>,
+}
+
+/// Blocking one-off work against the source, such as reading the process
+/// table or talking to Docker. Its result comes back as a message.
+pub type Job = Box<dyn FnOnce(&dyn ProcessSource) -> Message + Send>;
+
+enum Mode {
+    /// Requests go to the scanning thread; jobs get a thread each so a slow
+    /// one never holds up scans
+    Thread {
+        requests: Sender<(ScanRequest, u64)>,
+        results: Sender<Message>,
+    },
+    /// Requests and jobs run on the spot and post their result right away,
+    /// which keeps tests deterministic
+    Inline(Sender<Message>),
+}
+
+/// Handle to the scanner. At most one scan is in flight; requests made
+/// meanwhile collapse into a single follow-up using the newest settings.
+pub struct Worker {
+    source: Arc<dyn ProcessSource>,
+    mode: Mode,
+    in_flight: bool,
+    queued: Option<(ScanRequest, u64)>,
+    /// Requests made so far
+    generation: u64,
+}
+
+impl Worker {
+    /// Starts the thread; finished scans arrive on `results` as `Message::Scanned`
+    pub fn spawn(source: Arc<dyn ProcessSource>, results: Sender<Message>) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let thread_source = Arc::clone(&source);
+        let thread_results = results.clone();
+        thread::spawn(move || run(thread_source, rx, thread_results));
+        Self {
+            source,
+            mode: Mode::Thread { requests: tx, results },
+            in_flight: false,
+            queued: None,
+            generation: 0,
+        }
+    }
+
+    /// Scans on the caller's thread, still posting results to `results`
+    pub fn inline(source: Arc<dyn ProcessSource>, results: Sender<Message>) -> Self {
+        Self {
+            source,
+            mode: Mode::Inline(results),
+            in_flight: false,
+            queued: None,
+            generation: 0,
+        }
+    }
+
+    /// An inline worker over an empty fake whose results go nowhere
+    #[cfg(test)]
+    pub fn detached() -> Self {
+        let source = Arc::new(crate::source::FakeSource::new(Vec::new()));
+        Self::inline(source, mpsc::channel().0)
+    }
+
+    pub fn source(&self) -> &dyn ProcessSource {
+        self.source.as_ref()
+    }
+
+    /// Returns the request's generation; a `ScanOutput` with at least that
+    /// generation reflects it
+    pub fn request(&mut self, request: ScanRequest) -> u64 {
+        self.generation += 1;
+        if self.in_flight {
+            self.queued = Some((request, self.generation));
+        } else {
+            self.send(request, self.generation);
+        }
+        self.generation
+    }
+
+    pub fn run(&self, job: Job) {
+        match self.mode {
+            Mode::Thread { ref results, .. } => {
+                let source = Arc::clone(&self.source);
+                let results = results.clone();
+                thread::spawn(move || {
+                    let _ = results.send(job(source.as_ref()));
+                });
+            }
+            Mode::Inline(ref results) => {
+                let _ = results.send(job(self.source.as_ref()));
+            }
+        }
+    }
+
+    /// Marks the running scan done and starts the queued one, if any
+    pub fn finished(&mut self) {
+        self.in_flight = false;
+        if let Some((request, generation)) = self.queued.take() {
+            self.send(request, generation);
+        }
+    }
+
+    pub fn busy(&self) -> bool {
+        self.in_flight
+    }
+
+    fn send(&mut self, request: ScanRequest, generation: u64) {
+        self.in_flight = match self.mode {
+            Mode::Thread { ref requests, .. } => requests.send((request, generation)).is_ok(),
+            Mode::Inline(ref results) => {
+                let output = scan(self.source.as_ref(), request, generation);
+                results.send(Message::Scanned(Box::new(output))).is_ok()
+            }
+        };
+    }
+}
+
+fn run(source: Arc<dyn ProcessSource>, requests: Receiver<(ScanRequest, u64)>, results: Sender<Message>) {
+    while let Ok((request, generation)) = requests.recv() {
+        let output = scan(source.as_ref(), request, generation);
+        if results.send(Message::Scanned(Box::new(output))).is_err() {
+            break;
+        }
+    }
+}
+
+fn scan(source: &dyn ProcessSource, request: ScanRequest, generation: u64) -> ScanOutput {
+    let started = Instant::now();
+    let processes = source.scan(request);
+    ScanOutput {
+        generation,
+        processes,
+        cost: started.elapsed(),
+        tracked: source.tracked(),
+        machine_cpu: source.machine_cpu(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::FakeSource;
+
+    fn request(tree: bool) -> ScanRequest {
+        ScanRequest {
+            tree,
+            probe_http: false,
+            show_all: true,
+            unix: false,
+            memory_detail: false,
+        }
+    }
+
+    fn fake() -> Arc<dyn ProcessSource> {
+        Arc::new(FakeSource::new(vec![DevProcess::test("node", 3000)]))
+    }
+
+    #[test]
+    fn requests_while_busy_collapse_into_one() {
+        let (tx, rx) = mpsc::channel();
+        let mut worker = Worker::inline(fake(), tx);
+
+        worker.request(request(false));
+        worker.request(request(false));
+        assert_eq!(worker.request(request(true)), 3);
+        assert!(worker.busy());
+        assert_eq!(rx.try_iter().count(), 1);
+
+        worker.finished();
+        assert!(worker.busy());
+        match rx.try_recv().unwrap() {
+            Message::Scanned(output) => assert_eq!(output.generation, 3),
+            _ => panic!("expected a scan result"),
+        }
+
+        worker.finished();
+        assert!(!worker.busy());
+        assert_eq!(rx.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn spawned_worker_posts_results() {
+        let (tx, rx) = mpsc::channel();
+        let mut worker = Worker::spawn(fake(), tx);
+        worker.request(request(false));
+        match rx.recv_timeout(Duration::from_secs(10)).unwrap() {
+            Message::Scanned(output) => assert_eq!(output.processes.unwrap().len(), 1),
+            _ => panic!("expected a scan result"),
+        }
+    }
+}